@@ -0,0 +1,291 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+//! Output for status bars: a short summary of the next event, for
+//! waybar as JSON with the calendar as tooltip, as i3bar protocol and
+//! as plain text for polybar.
+
+use crate::cli::Cli;
+use crate::context::Context;
+use crate::error::Error;
+use crate::events::EventInstance;
+use crate::template::filters::format_date;
+use crate::template::render;
+use chrono::prelude::*;
+use chrono::Duration;
+use serde_json::json;
+use std::io::{self, Write};
+
+/// How many days to look ahead for the next event.
+const LOOKAHEAD_DAYS: i64 = 30;
+
+/// How often the i3bar status line is updated.
+const I3BAR_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+const DATE_FORMAT: &str = "%a %-d %b";
+
+/// The output of `ctx` for the status bar `format` (`waybar` or
+/// `polybar`), or a single status line for `i3bar`.
+pub fn render_bar(ctx: &Context, format: &str) -> Result<String, Error> {
+    let (text, class) = summary(ctx, ctx.now);
+    Ok(match format {
+        "waybar" => {
+            let calendar = render(ctx)?;
+            json!({
+                "text": escape(&text),
+                "tooltip": format!("<tt>{}</tt>", ansi_to_pango(calendar.trim_end())),
+                "class": class,
+            })
+            .to_string()
+        }
+        "i3bar" => json!([{ "name": "carl", "full_text": text, "urgent": class == "now" }]).to_string(),
+        _ => text,
+    })
+}
+
+/// Print the i3bar protocol, with a new status line every minute.
+#[cfg(not(tarpaulin_include))]
+pub fn run_i3bar(opts: Cli) -> Result<(), Error> {
+    let mut stdout = io::stdout();
+    writeln!(stdout, "{}", json!({ "version": 1 }))?;
+    writeln!(stdout, "[")?;
+    loop {
+        let line = match Context::from_opts(opts.clone()).and_then(|ctx| render_bar(&ctx, "i3bar")) {
+            Ok(line) => line,
+            Err(e) => json!([{ "name": "carl", "full_text": e.to_string(), "urgent": true }]).to_string(),
+        };
+        writeln!(stdout, "{},", line)?;
+        stdout.flush()?;
+        std::thread::sleep(I3BAR_INTERVAL);
+    }
+}
+
+/// The text and the class of the status: the next event that has not
+/// yet started, if there is one in the next days.
+fn summary(ctx: &Context, now: NaiveDateTime) -> (String, &'static str) {
+    let instances = ctx.upcoming_event_instances(now, LOOKAHEAD_DAYS);
+    let next = instances.first();
+    let date = |date: NaiveDate| format_date(&date.to_string(), Some(DATE_FORMAT), ctx.locale, ctx.calendar).unwrap_or_default();
+    match next {
+        None => (date(now.date()), "none"),
+        Some(instance) if instance.date == now.date() => (describe(instance, None), today_class(instance, now)),
+        Some(instance) => (describe(instance, Some(date(instance.date))), "upcoming"),
+    }
+}
+
+fn describe(instance: &EventInstance, date: Option<String>) -> String {
+    let time = instance.start_time().map(|time| time.format("%H:%M").to_string());
+    [date, time, Some(instance.event.summary.clone())].into_iter().flatten().collect::<Vec<String>>().join(" ")
+}
+
+/// `now` if the event starts within the next 15 minutes, `today`
+/// otherwise.
+fn today_class(instance: &EventInstance, now: NaiveDateTime) -> &'static str {
+    match instance.start_time() {
+        Some(time) if time - now.time() <= Duration::minutes(15) => "now",
+        _ => "today",
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// The text attributes set by ANSI escape sequences.
+#[derive(Default, Clone)]
+struct Attributes {
+    bold: bool,
+    dimmed: bool,
+    italic: bool,
+    underline: bool,
+    reverse: bool,
+    hidden: bool,
+    strikethrough: bool,
+    foreground: Option<String>,
+    background: Option<String>,
+}
+
+impl Attributes {
+    /// Apply the parameters of a SGR escape sequence.
+    fn apply(&mut self, parameters: &[u8]) {
+        let mut parameters = parameters.iter();
+        while let Some(parameter) = parameters.next() {
+            match parameter {
+                0 => *self = Attributes::default(),
+                1 => self.bold = true,
+                2 => self.dimmed = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                7 => self.reverse = true,
+                8 => self.hidden = true,
+                9 => self.strikethrough = true,
+                30..=37 => self.foreground = Some(ansi_color(parameter - 30)),
+                40..=47 => self.background = Some(ansi_color(parameter - 40)),
+                90..=97 => self.foreground = Some(ansi_color(parameter - 90 + 8)),
+                100..=107 => self.background = Some(ansi_color(parameter - 100 + 8)),
+                38 | 48 => {
+                    let color = match parameters.next() {
+                        Some(5) => parameters.next().map(|color| ansi_color(*color)),
+                        Some(2) => match (parameters.next(), parameters.next(), parameters.next()) {
+                            (Some(r), Some(g), Some(b)) => Some(format!("#{:02x}{:02x}{:02x}", r, g, b)),
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                    if *parameter == 38 {
+                        self.foreground = color;
+                    } else {
+                        self.background = color;
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// The attributes of a Pango `span` element.
+    fn span(&self) -> String {
+        let (mut foreground, mut background) = (self.foreground.clone(), self.background.clone());
+        if self.reverse {
+            (foreground, background) = (Some(background.unwrap_or(String::from("black"))), Some(foreground.unwrap_or(String::from("white"))));
+        }
+        let attributes = [
+            self.bold.then(|| String::from("weight=\"bold\"")),
+            self.dimmed.then(|| String::from("alpha=\"50%\"")),
+            self.italic.then(|| String::from("style=\"italic\"")),
+            self.underline.then(|| String::from("underline=\"single\"")),
+            self.strikethrough.then(|| String::from("strikethrough=\"true\"")),
+            foreground.map(|color| format!("foreground=\"{}\"", color)),
+            background.map(|color| format!("background=\"{}\"", color)),
+        ];
+        attributes.into_iter().flatten().collect::<Vec<String>>().join(" ")
+    }
+}
+
+/// The color with the number `color` of the 256 color palette.
+fn ansi_color(color: u8) -> String {
+    const NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+    const BRIGHT: [&str; 8] = ["#808080", "#ff0000", "#00ff00", "#ffff00", "#0000ff", "#ff00ff", "#00ffff", "#ffffff"];
+    match color {
+        0..=7 => NAMES[color as usize].to_string(),
+        8..=15 => BRIGHT[color as usize - 8].to_string(),
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let color = color - 16;
+            format!("#{:02x}{:02x}{:02x}", level(color / 36), level(color / 6 % 6), level(color % 6))
+        }
+        _ => {
+            let gray = 8 + (color - 232) * 10;
+            format!("#{:02x}{:02x}{:02x}", gray, gray, gray)
+        }
+    }
+}
+
+/// Convert text styled with ANSI escape sequences to Pango markup.
+/// Hidden text is replaced by spaces, to keep the layout.
+fn ansi_to_pango(text: &str) -> String {
+    let mut markup = String::new();
+    let mut attributes = Attributes::default();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let end = rest.find('\x1b').unwrap_or(rest.len());
+        let (plain, sequence) = rest.split_at(end);
+        if !plain.is_empty() {
+            let span = attributes.span();
+            if attributes.hidden {
+                markup.push_str(&" ".repeat(plain.chars().count()));
+            } else if span.is_empty() {
+                markup.push_str(&escape(plain));
+            } else {
+                markup.push_str(&format!("<span {}>{}</span>", span, escape(plain)));
+            }
+        }
+        rest = sequence;
+        if let Some(sequence) = rest.strip_prefix("\x1b[") {
+            let length = sequence.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(sequence.len());
+            if sequence[length..].starts_with('m') {
+                let parameters: Vec<u8> = sequence[..length].split(';').map(|parameter| parameter.parse().unwrap_or(0)).collect();
+                attributes.apply(&parameters);
+            }
+            rest = sequence.get(length + 1..).unwrap_or_default();
+        } else if !rest.is_empty() {
+            rest = &rest[1..];
+        }
+    }
+    markup
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Style;
+    use crate::events::{Event, EventDateTime};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn instance(date: NaiveDate, time: Option<NaiveTime>, summary: &str) -> EventInstance {
+        let start = match time {
+            Some(time) => EventDateTime::DateTime {
+                date_time: date.and_time(time),
+                offset: None,
+            },
+            None => EventDateTime::Date(date),
+        };
+        EventInstance {
+            date,
+            event: Event {
+                start,
+                end: start,
+                rrulesets: vec![],
+                summary: String::from(summary),
+                categories: vec![],
+            },
+            style: Style::default(),
+        }
+    }
+
+    #[test]
+    fn test_describe() {
+        let time = NaiveTime::from_hms_opt(14, 0, 0);
+        assert_eq!(describe(&instance(date(2025, 9, 9), time, "Meeting"), None), "14:00 Meeting");
+        assert_eq!(describe(&instance(date(2025, 9, 12), None, "Holiday"), Some(String::from("Fri 12 Sep"))), "Fri 12 Sep Holiday");
+    }
+    #[test]
+    fn test_today_class() {
+        let now = date(2025, 9, 9).and_hms_opt(13, 50, 0).unwrap();
+        assert_eq!(today_class(&instance(date(2025, 9, 9), NaiveTime::from_hms_opt(14, 0, 0), ""), now), "now");
+        assert_eq!(today_class(&instance(date(2025, 9, 9), NaiveTime::from_hms_opt(15, 0, 0), ""), now), "today");
+        assert_eq!(today_class(&instance(date(2025, 9, 9), None, ""), now), "today");
+    }
+    #[test]
+    fn test_ansi_to_pango() {
+        assert_eq!(ansi_to_pango("a < b"), "a &lt; b");
+        assert_eq!(ansi_to_pango("\x1b[1m\x1b[30m\x1b[42m 9\x1b[0m 10"), "<span weight=\"bold\" foreground=\"black\" background=\"green\"> 9</span> 10");
+        assert_eq!(ansi_to_pango("\x1b[2m\x1b[8m31\x1b[0m"), "  ");
+        assert_eq!(ansi_to_pango("\x1b[38;2;255;0;128mx\x1b[0m"), "<span foreground=\"#ff0080\">x</span>");
+        assert_eq!(ansi_to_pango("\x1b[7mx\x1b[0m"), "<span foreground=\"black\" background=\"white\">x</span>");
+    }
+    #[test]
+    fn test_ansi_color() {
+        assert_eq!(ansi_color(1), "red");
+        assert_eq!(ansi_color(196), "#ff0000");
+        assert_eq!(ansi_color(232), "#080808");
+    }
+    #[test]
+    fn test_render_bar() {
+        let mut ctx = Context::default();
+        ctx.today = date(2025, 9, 9);
+        ctx.now = ctx.today.and_hms_opt(12, 0, 0).unwrap();
+        ctx.set_date(ctx.today).unwrap();
+        let waybar: serde_json::Value = serde_json::from_str(&render_bar(&ctx, "waybar").unwrap()).unwrap();
+        assert_eq!(waybar["text"], "Tue 9 Sep");
+        assert_eq!(waybar["class"], "none");
+        assert!(waybar["tooltip"].as_str().unwrap().starts_with("<tt>"));
+        assert_eq!(render_bar(&ctx, "polybar").unwrap(), "Tue 9 Sep");
+        let i3bar: serde_json::Value = serde_json::from_str(&render_bar(&ctx, "i3bar").unwrap()).unwrap();
+        assert_eq!(i3bar[0]["full_text"], "Tue 9 Sep");
+    }
+}
@@ -0,0 +1,141 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+//! The hebrew calendar, following "Calendrical Calculations" by
+//! Reingold and Dershowitz. The months are numbered from Nisan, the
+//! year starts with Tishri, the seventh month. In leap years, Adar is
+//! the twelfth and Adar II the thirteenth month.
+
+use super::{CalendarDate, CalendarSystem};
+use chrono::prelude::*;
+
+/// 1 Tishri AM 1, in days from 0001-01-01 of the gregorian calendar
+/// (counting from one).
+const EPOCH: i64 = -1373427;
+
+const MONTHS: [&str; 13] = [
+    "Nisan", "Iyyar", "Sivan", "Tammuz", "Av", "Elul", "Tishri", "Heshvan", "Kislev", "Tevet", "Shevat", "Adar", "Adar II",
+];
+
+pub struct Hebrew;
+
+fn is_leap_year(year: i64) -> bool {
+    (7 * year + 1).rem_euclid(19) < 7
+}
+
+fn last_month(year: i64) -> i64 {
+    if is_leap_year(year) {
+        13
+    } else {
+        12
+    }
+}
+
+/// The days from the epoch to the new year, without the delays that
+/// keep the year within its allowed length.
+fn elapsed_days(year: i64) -> i64 {
+    let months_elapsed = (235 * year - 234).div_euclid(19);
+    let parts_elapsed = 12084 + 13753 * months_elapsed;
+    let day = 29 * months_elapsed + parts_elapsed.div_euclid(25920);
+    if (3 * (day + 1)).rem_euclid(7) < 3 {
+        day + 1
+    } else {
+        day
+    }
+}
+
+fn year_length_correction(year: i64) -> i64 {
+    let (ny0, ny1, ny2) = (elapsed_days(year - 1), elapsed_days(year), elapsed_days(year + 1));
+    if ny2 - ny1 == 356 {
+        2
+    } else if ny1 - ny0 == 382 {
+        1
+    } else {
+        0
+    }
+}
+
+fn new_year(year: i64) -> i64 {
+    EPOCH + elapsed_days(year) + year_length_correction(year)
+}
+
+fn days_in_year(year: i64) -> i64 {
+    new_year(year + 1) - new_year(year)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    let long_heshvan = matches!(days_in_year(year), 355 | 385);
+    let short_kislev = matches!(days_in_year(year), 353 | 383);
+    match month {
+        2 | 4 | 6 | 10 | 13 => 29,
+        12 if !is_leap_year(year) => 29,
+        8 if !long_heshvan => 29,
+        9 if short_kislev => 29,
+        _ => 30,
+    }
+}
+
+fn fixed(year: i64, month: i64, day: i64) -> i64 {
+    let months_before: i64 = if month < 7 {
+        (7..=last_month(year)).chain(1..month).map(|month| days_in_month(year, month)).sum()
+    } else {
+        (7..month).map(|month| days_in_month(year, month)).sum()
+    };
+    new_year(year) + months_before + day - 1
+}
+
+impl CalendarSystem for Hebrew {
+    fn to_calendar_date(&self, date: NaiveDate) -> Option<CalendarDate> {
+        let date = i64::from(date.num_days_from_ce());
+        if date < EPOCH {
+            return None;
+        }
+        let mut year = ((date - EPOCH) as f64 / (35975351.0 / 98496.0)).floor() as i64;
+        while new_year(year + 1) <= date {
+            year += 1;
+        }
+        let start = if date < fixed(year, 1, 1) { 7 } else { 1 };
+        let month = (start..=last_month(year)).find(|month| date <= fixed(year, *month, days_in_month(year, *month)))?;
+        let day = date - fixed(year, month, 1) + 1;
+        Some(CalendarDate::new(year as i32, month as u32, day as u32))
+    }
+
+    fn to_date(&self, date: CalendarDate) -> Option<NaiveDate> {
+        let (year, month, day) = (i64::from(date.year), i64::from(date.month), i64::from(date.day));
+        if year < 1 || month < 1 || month > last_month(year) || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+        NaiveDate::from_num_days_from_ce_opt(i32::try_from(fixed(year, month, day)).ok()?)
+    }
+
+    fn month_name(&self, date: CalendarDate) -> &'static str {
+        if date.month == 12 && is_leap_year(i64::from(date.year)) {
+            return "Adar I";
+        }
+        MONTHS[date.month as usize - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hebrew_dates() {
+        let rosh_hashanah = NaiveDate::from_ymd_opt(2025, 9, 23).unwrap();
+        assert_eq!(Hebrew.to_calendar_date(rosh_hashanah), Some(CalendarDate::new(5786, 7, 1)));
+        assert_eq!(Hebrew.to_date(CalendarDate::new(5785, 1, 15)), NaiveDate::from_ymd_opt(2025, 4, 13));
+        assert_eq!(Hebrew.month_name(CalendarDate::new(5784, 12, 1)), "Adar I");
+        assert_eq!(Hebrew.month_name(CalendarDate::new(5785, 12, 1)), "Adar");
+        assert_eq!(Hebrew.to_date(CalendarDate::new(5785, 13, 1)), None);
+    }
+    #[test]
+    fn test_hebrew_roundtrip() {
+        let mut date = NaiveDate::from_ymd_opt(1000, 1, 1).unwrap();
+        while date.year() < 2100 {
+            assert_eq!(Hebrew.to_date(Hebrew.to_calendar_date(date).unwrap()), Some(date));
+            date += chrono::Duration::days(17);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+//! The tabular islamic calendar, following "Calendrical Calculations"
+//! by Reingold and Dershowitz. The months of the religious calendar
+//! start with the sighting of the new moon, so the dates can differ by
+//! a day or two.
+
+use super::{CalendarDate, CalendarSystem};
+use chrono::prelude::*;
+
+/// 1 Muharram 1 AH (July 16, 622 in the julian calendar), in days
+/// from 0001-01-01 of the gregorian calendar (counting from one).
+const EPOCH: i64 = 227015;
+
+const MONTHS: [&str; 12] = [
+    "Muharram",
+    "Safar",
+    "Rabi' al-awwal",
+    "Rabi' al-thani",
+    "Jumada al-awwal",
+    "Jumada al-thani",
+    "Rajab",
+    "Sha'ban",
+    "Ramadan",
+    "Shawwal",
+    "Dhu al-Qi'dah",
+    "Dhu al-Hijjah",
+];
+
+pub struct Islamic;
+
+fn fixed(year: i64, month: i64, day: i64) -> i64 {
+    day + 29 * (month - 1) + (6 * month - 1).div_euclid(11) + (year - 1) * 354 + (3 + 11 * year).div_euclid(30) + EPOCH - 1
+}
+
+impl CalendarSystem for Islamic {
+    fn to_calendar_date(&self, date: NaiveDate) -> Option<CalendarDate> {
+        let date = i64::from(date.num_days_from_ce());
+        if date < EPOCH {
+            return None;
+        }
+        let year = (30 * (date - EPOCH) + 10646).div_euclid(10631);
+        let month = (11 * (date - fixed(year, 1, 1)) + 330).div_euclid(325);
+        let day = date - fixed(year, month, 1) + 1;
+        Some(CalendarDate::new(year as i32, month as u32, day as u32))
+    }
+
+    fn to_date(&self, date: CalendarDate) -> Option<NaiveDate> {
+        let (year, month, day) = (i64::from(date.year), i64::from(date.month), i64::from(date.day));
+        let leap = (14 + 11 * year).rem_euclid(30) < 11;
+        let days_in_month = if month % 2 == 1 || month == 12 && leap { 30 } else { 29 };
+        if year < 1 || !(1..=12).contains(&month) || day < 1 || day > days_in_month {
+            return None;
+        }
+        NaiveDate::from_num_days_from_ce_opt(i32::try_from(fixed(year, month, day)).ok()?)
+    }
+
+    fn month_name(&self, date: CalendarDate) -> &'static str {
+        MONTHS[date.month as usize - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_islamic_dates() {
+        // Sample date from Calendrical Calculations
+        let date = NaiveDate::from_ymd_opt(1945, 11, 12).unwrap();
+        assert_eq!(Islamic.to_calendar_date(date), Some(CalendarDate::new(1364, 12, 6)));
+        assert_eq!(Islamic.to_date(CalendarDate::new(1364, 12, 6)), Some(date));
+        assert_eq!(Islamic.to_calendar_date(NaiveDate::from_ymd_opt(600, 1, 1).unwrap()), None);
+        assert_eq!(Islamic.month_name(CalendarDate::new(1446, 9, 1)), "Ramadan");
+    }
+    #[test]
+    fn test_islamic_roundtrip() {
+        let mut date = NaiveDate::from_ymd_opt(700, 1, 1).unwrap();
+        while date.year() < 2100 {
+            assert_eq!(Islamic.to_date(Islamic.to_calendar_date(date).unwrap()), Some(date));
+            date += chrono::Duration::days(13);
+        }
+    }
+}
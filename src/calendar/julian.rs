@@ -0,0 +1,63 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+//! Conversion between the gregorian and the julian calendar, using the
+//! julian day number.
+
+use super::CalendarDate;
+use chrono::prelude::*;
+
+/// The julian day number of the day before 0001-01-01 of the gregorian
+/// calendar, which is day zero of `num_days_from_ce`.
+const JDN_OFFSET: i64 = 1721425;
+
+pub(super) fn to_julian(date: NaiveDate) -> CalendarDate {
+    let c = i64::from(date.num_days_from_ce()) + JDN_OFFSET + 32082;
+    let d = (4 * c + 3).div_euclid(1461);
+    let e = c - (1461 * d).div_euclid(4);
+    let m = (5 * e + 2).div_euclid(153);
+    let day = e - (153 * m + 2).div_euclid(5) + 1;
+    let month = m + 3 - 12 * (m / 10);
+    let year = d - 4800 + m / 10;
+    CalendarDate::new(year as i32, month as u32, day as u32)
+}
+
+pub(super) fn from_julian(date: CalendarDate) -> Option<NaiveDate> {
+    let days_in_month = match date.month {
+        2 if date.year.rem_euclid(4) == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return None,
+    };
+    if date.day < 1 || date.day > days_in_month {
+        return None;
+    }
+    let a = (14 - i64::from(date.month)) / 12;
+    let y = i64::from(date.year) + 4800 - a;
+    let m = i64::from(date.month) + 12 * a - 3;
+    let jdn = i64::from(date.day) + (153 * m + 2) / 5 + 365 * y + y.div_euclid(4) - 32083;
+    NaiveDate::from_num_days_from_ce_opt(i32::try_from(jdn - JDN_OFFSET).ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_julian_roundtrip() {
+        let mut date = NaiveDate::from_ymd_opt(1, 1, 1).unwrap();
+        while date.year() < 2100 {
+            assert_eq!(from_julian(to_julian(date)), Some(date));
+            date += chrono::Duration::days(97);
+        }
+    }
+    #[test]
+    fn test_julian_dates() {
+        assert_eq!(to_julian(NaiveDate::from_ymd_opt(1582, 10, 15).unwrap()), CalendarDate::new(1582, 10, 5));
+        assert_eq!(to_julian(NaiveDate::from_ymd_opt(1, 1, 1).unwrap()), CalendarDate::new(1, 1, 3));
+        assert_eq!(from_julian(CalendarDate::new(1700, 2, 29)), NaiveDate::from_ymd_opt(1700, 3, 11));
+        assert_eq!(from_julian(CalendarDate::new(1701, 2, 29)), None);
+    }
+}
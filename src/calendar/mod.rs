@@ -0,0 +1,278 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+//! Calendar systems. Internally every date is a `chrono::NaiveDate`,
+//! which identifies a day using the proleptic gregorian calendar. A
+//! calendar system maps those days to the years, months and days of
+//! the calendar. The months of the calendar grid are built using a
+//! [`Calendar`], other calendar systems (like the hebrew, the islamic or
+//! the persian calendar) can be used to show a secondary date.
+
+mod hebrew;
+mod islamic;
+mod julian;
+mod persian;
+
+pub use hebrew::Hebrew;
+pub use islamic::Islamic;
+pub use persian::Persian;
+
+use chrono::prelude::*;
+use chrono::Duration;
+use serde::Serialize;
+
+/// A date in a calendar system.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CalendarDate {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl CalendarDate {
+    pub fn new(year: i32, month: u32, day: u32) -> Self {
+        CalendarDate { year, month, day }
+    }
+}
+
+pub trait CalendarSystem {
+    /// The date in this calendar system, if `date` is in the range the
+    /// calendar system supports.
+    fn to_calendar_date(&self, date: NaiveDate) -> Option<CalendarDate>;
+    /// The day of a date of this calendar system, if it exists.
+    fn to_date(&self, date: CalendarDate) -> Option<NaiveDate>;
+    /// The english name of the month of a date of this calendar system.
+    fn month_name(&self, date: CalendarDate) -> &'static str;
+}
+
+const GREGORIAN_MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November", "December",
+];
+
+/// The calendar used for the calendar grid: the gregorian calendar, the
+/// julian calendar or the julian calendar until the reform, with the
+/// gregorian calendar starting on the given day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Calendar {
+    #[default]
+    Gregorian,
+    Julian,
+    Reform(NaiveDate),
+}
+
+impl Calendar {
+    /// The reform of 1582, used in most catholic countries.
+    pub const REFORM_1582: Calendar = Calendar::Reform(NaiveDate::from_ymd_opt(1582, 10, 15).unwrap());
+    /// The reform of 1752, used in Great Britain and its colonies.
+    pub const REFORM_1752: Calendar = Calendar::Reform(NaiveDate::from_ymd_opt(1752, 9, 14).unwrap());
+
+    /// The calendar with the given name, as used by `--reform`: the year
+    /// of a reform, `gregorian` or `julian`.
+    pub fn from_name(name: &str) -> Option<Calendar> {
+        match name.to_lowercase().as_str() {
+            "1582" => Some(Calendar::REFORM_1582),
+            "1752" => Some(Calendar::REFORM_1752),
+            "gregorian" => Some(Calendar::Gregorian),
+            "julian" => Some(Calendar::Julian),
+            _ => None,
+        }
+    }
+
+    fn is_julian(&self, date: NaiveDate) -> bool {
+        match self {
+            Calendar::Gregorian => false,
+            Calendar::Julian => true,
+            Calendar::Reform(first_gregorian_day) => date < *first_gregorian_day,
+        }
+    }
+
+    /// The date in this calendar. Every day can be represented in the
+    /// calendars used for the grid.
+    pub fn date(&self, date: NaiveDate) -> CalendarDate {
+        if self.is_julian(date) {
+            julian::to_julian(date)
+        } else {
+            CalendarDate::new(date.year(), date.month(), date.day())
+        }
+    }
+
+    /// The first day of the month of `date`.
+    pub fn first_day_of_month(&self, date: NaiveDate) -> NaiveDate {
+        let day = self.date(date);
+        self.to_date(CalendarDate::new(day.year, day.month, 1)).unwrap_or(date)
+    }
+
+    /// The last day of the month of `date`.
+    pub fn last_day_of_month(&self, date: NaiveDate) -> NaiveDate {
+        let day = self.date(date);
+        let (year, month) = if day.month == 12 { (day.year + 1, 1) } else { (day.year, day.month + 1) };
+        self.to_date(CalendarDate::new(year, month, 1)).map_or(date, |first| first - Duration::days(1))
+    }
+
+    /// The first day of the year of `date`.
+    pub fn first_day_of_year(&self, date: NaiveDate) -> NaiveDate {
+        self.to_date(CalendarDate::new(self.date(date).year, 1, 1)).unwrap_or(date)
+    }
+
+    /// The last day of the year of `date`.
+    pub fn last_day_of_year(&self, date: NaiveDate) -> NaiveDate {
+        self.to_date(CalendarDate::new(self.date(date).year + 1, 1, 1)).map_or(date, |first| first - Duration::days(1))
+    }
+
+    /// The same day of the month `months` months after (or before, if
+    /// negative) the month of `date`. Days that do not exist in that
+    /// month are moved to the last day of the month, days skipped by
+    /// the reform to the first day after the reform.
+    pub fn add_months(&self, date: NaiveDate, months: i32) -> NaiveDate {
+        let day = self.date(date);
+        let month = day.year * 12 + day.month as i32 - 1 + months;
+        let (year, month) = (month.div_euclid(12), month.rem_euclid(12) as u32 + 1);
+        let Some(first) = self.to_date(CalendarDate::new(year, month, 1)) else {
+            return date;
+        };
+        let last = self.last_day_of_month(first);
+        (day.day..=self.date(last).day)
+            .find_map(|day| self.to_date(CalendarDate::new(year, month, day)))
+            .unwrap_or(last)
+    }
+
+    /// The day of the year of `date`, counted like util-linux `cal`
+    /// does: the days of the preceding months plus the day of the
+    /// month. In the year of the reform the days that were skipped are
+    /// still counted, so the numbers are not consecutive.
+    pub fn ordinal(&self, date: NaiveDate) -> u32 {
+        let day = self.date(date);
+        let julian_leap_years = match self {
+            Calendar::Gregorian => false,
+            Calendar::Julian => true,
+            Calendar::Reform(first_gregorian_day) => day.year <= first_gregorian_day.year(),
+        };
+        let leap = if julian_leap_years {
+            day.year.rem_euclid(4) == 0
+        } else {
+            NaiveDate::from_ymd_opt(day.year, 2, 29).is_some()
+        };
+        let days_before: u32 = (1..day.month)
+            .map(|month| match month {
+                2 if leap => 29,
+                2 => 28,
+                4 | 6 | 9 | 11 => 30,
+                _ => 31,
+            })
+            .sum();
+        days_before + day.day
+    }
+}
+
+impl CalendarSystem for Calendar {
+    fn to_calendar_date(&self, date: NaiveDate) -> Option<CalendarDate> {
+        Some(self.date(date))
+    }
+
+    fn to_date(&self, date: CalendarDate) -> Option<NaiveDate> {
+        let gregorian = NaiveDate::from_ymd_opt(date.year, date.month, date.day).filter(|day| !self.is_julian(*day));
+        let julian = || julian::from_julian(date).filter(|day| self.is_julian(*day));
+        match self {
+            Calendar::Gregorian => gregorian,
+            Calendar::Julian => julian(),
+            // Around the reform, the days missing in the gregorian
+            // calendar and those missing in the julian calendar overlap
+            Calendar::Reform(_) => gregorian.or_else(julian),
+        }
+    }
+
+    fn month_name(&self, date: CalendarDate) -> &'static str {
+        GREGORIAN_MONTHS[date.month as usize - 1]
+    }
+}
+
+/// The calendar system with the given name, for the calendars that can
+/// be used for secondary dates.
+pub fn calendar_system(name: &str) -> Option<Box<dyn CalendarSystem>> {
+    match name.to_lowercase().as_str() {
+        "gregorian" => Some(Box::new(Calendar::Gregorian)),
+        "julian" => Some(Box::new(Calendar::Julian)),
+        "hebrew" => Some(Box::new(Hebrew)),
+        "islamic" => Some(Box::new(Islamic)),
+        "persian" => Some(Box::new(Persian)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_gregorian() {
+        let calendar = Calendar::Gregorian;
+        assert_eq!(calendar.date(date(2025, 9, 9)), CalendarDate::new(2025, 9, 9));
+        assert_eq!(calendar.first_day_of_month(date(2025, 9, 9)), date(2025, 9, 1));
+        assert_eq!(calendar.last_day_of_month(date(2024, 2, 9)), date(2024, 2, 29));
+        assert_eq!(calendar.last_day_of_year(date(2024, 2, 9)), date(2024, 12, 31));
+    }
+    #[test]
+    fn test_julian() {
+        let calendar = Calendar::Julian;
+        assert_eq!(calendar.date(date(2025, 9, 9)), CalendarDate::new(2025, 8, 27));
+        assert_eq!(calendar.first_day_of_month(date(2025, 9, 9)), date(2025, 8, 14));
+        // 1900 is a leap year in the julian calendar
+        assert_eq!(calendar.last_day_of_month(date(1900, 3, 1)), date(1900, 3, 13));
+        assert_eq!(calendar.to_date(CalendarDate::new(1900, 2, 29)), Some(date(1900, 3, 13)));
+        assert_eq!(calendar.month_name(calendar.date(date(1900, 3, 13))), "February");
+    }
+    #[test]
+    fn test_reform_1752() {
+        let calendar = Calendar::REFORM_1752;
+        assert_eq!(calendar.date(date(1752, 9, 13)), CalendarDate::new(1752, 9, 2));
+        assert_eq!(calendar.date(date(1752, 9, 14)), CalendarDate::new(1752, 9, 14));
+        assert_eq!(calendar.first_day_of_month(date(1752, 9, 20)), date(1752, 9, 12));
+        assert_eq!(calendar.last_day_of_month(date(1752, 9, 12)), date(1752, 9, 30));
+        assert_eq!(calendar.to_date(CalendarDate::new(1752, 9, 5)), None);
+        assert_eq!(calendar.first_day_of_year(date(1752, 9, 20)), date(1752, 1, 12));
+        assert_eq!(calendar.last_day_of_month(date(1700, 2, 20)), date(1700, 3, 11));
+    }
+    #[test]
+    fn test_reform_1582() {
+        let calendar = Calendar::REFORM_1582;
+        assert_eq!(calendar.to_date(CalendarDate::new(1582, 10, 4)), Some(date(1582, 10, 14)));
+        assert_eq!(calendar.to_date(CalendarDate::new(1582, 10, 15)), Some(date(1582, 10, 15)));
+        assert_eq!(calendar.to_date(CalendarDate::new(1582, 10, 10)), None);
+    }
+    #[test]
+    fn test_ordinal() {
+        assert_eq!(Calendar::Gregorian.ordinal(date(2024, 12, 31)), 366);
+        assert_eq!(Calendar::Julian.ordinal(date(1900, 3, 13)), 60);
+        let calendar = Calendar::REFORM_1752;
+        assert_eq!(calendar.ordinal(date(1752, 9, 13)), 246);
+        assert_eq!(calendar.ordinal(date(1752, 9, 14)), 258);
+        assert_eq!(calendar.ordinal(date(1752, 12, 31)), 366);
+        let calendar = Calendar::REFORM_1582;
+        assert_eq!(calendar.ordinal(date(1582, 10, 14)), 277);
+        assert_eq!(calendar.ordinal(date(1582, 10, 15)), 288);
+    }
+    #[test]
+    fn test_add_months() {
+        assert_eq!(Calendar::Gregorian.add_months(date(2025, 1, 31), 1), date(2025, 2, 28));
+        assert_eq!(Calendar::Gregorian.add_months(date(2025, 1, 31), -2), date(2024, 11, 30));
+        let calendar = Calendar::REFORM_1752;
+        assert_eq!(calendar.add_months(date(1752, 8, 16), 1), date(1752, 9, 14));
+        assert_eq!(calendar.add_months(date(1752, 9, 20), -1), date(1752, 8, 31));
+    }
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Calendar::from_name("1752"), Some(Calendar::REFORM_1752));
+        assert_eq!(Calendar::from_name("Julian"), Some(Calendar::Julian));
+        assert_eq!(Calendar::from_name("1600"), None);
+    }
+    #[test]
+    fn test_calendar_system() {
+        assert!(calendar_system("Hebrew").is_some());
+        assert!(calendar_system("klingon").is_none());
+    }
+}
@@ -0,0 +1,110 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+//! The persian (solar hijri) calendar, using the algorithm by Kazimierz
+//! Borkowski, which matches the astronomical calendar for the years
+//! -61 to 3177.
+
+use super::{CalendarDate, CalendarSystem};
+use chrono::prelude::*;
+use chrono::Duration;
+
+/// The years in which the pattern of leap years changes.
+const BREAKS: [i32; 20] = [-61, 9, 38, 199, 426, 686, 756, 818, 1111, 1181, 1210, 1635, 2060, 2097, 2192, 2262, 2324, 2394, 2456, 3178];
+
+const MONTHS: [&str; 12] = [
+    "Farvardin", "Ordibehesht", "Khordad", "Tir", "Mordad", "Shahrivar", "Mehr", "Aban", "Azar", "Dey", "Bahman", "Esfand",
+];
+
+pub struct Persian;
+
+/// The first day of the persian year `year` (Nowruz) and if the year is
+/// a leap year.
+fn new_year(year: i32) -> Option<(NaiveDate, bool)> {
+    if year < BREAKS[0] || year >= BREAKS[BREAKS.len() - 1] {
+        return None;
+    }
+    let gregorian_year = year + 621;
+    let mut leap_persian = -14;
+    let mut previous_break = BREAKS[0];
+    let mut jump = 0;
+    for current_break in &BREAKS[1..] {
+        jump = current_break - previous_break;
+        if year < *current_break {
+            break;
+        }
+        leap_persian += jump / 33 * 8 + jump % 33 / 4;
+        previous_break = *current_break;
+    }
+    let mut n = year - previous_break;
+    leap_persian += n / 33 * 8 + (n % 33 + 3) / 4;
+    if jump % 33 == 4 && jump - n == 4 {
+        leap_persian += 1;
+    }
+    let leap_gregorian = gregorian_year / 4 - (gregorian_year / 100 + 1) * 3 / 4 - 150;
+    let march = 20 + leap_persian - leap_gregorian;
+    if jump - n < 6 {
+        n = n - jump + (jump + 4) / 33 * 33;
+    }
+    let leap = ((n + 1) % 33 - 1) % 4 == 0;
+    Some((NaiveDate::from_ymd_opt(gregorian_year, 3, 1)? + Duration::days(i64::from(march) - 1), leap))
+}
+
+impl CalendarSystem for Persian {
+    fn to_calendar_date(&self, date: NaiveDate) -> Option<CalendarDate> {
+        let mut year = date.year() - 621;
+        let mut first_day = new_year(year)?.0;
+        if date < first_day {
+            year -= 1;
+            first_day = new_year(year)?.0;
+        }
+        let days = (date - first_day).num_days();
+        let (month, day) = if days < 186 { (1 + days / 31, days % 31 + 1) } else { (7 + (days - 186) / 30, (days - 186) % 30 + 1) };
+        Some(CalendarDate::new(year, month as u32, day as u32))
+    }
+
+    fn to_date(&self, date: CalendarDate) -> Option<NaiveDate> {
+        let (first_day, leap) = new_year(date.year)?;
+        let days_in_month = match date.month {
+            1..=6 => 31,
+            7..=11 => 30,
+            12 if leap => 30,
+            12 => 29,
+            _ => return None,
+        };
+        if date.day < 1 || date.day > days_in_month {
+            return None;
+        }
+        let month = i64::from(date.month);
+        let days = (month - 1) * 31 - month / 7 * (month - 7) + i64::from(date.day) - 1;
+        Some(first_day + Duration::days(days))
+    }
+
+    fn month_name(&self, date: CalendarDate) -> &'static str {
+        MONTHS[date.month as usize - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_persian_dates() {
+        let nowruz = NaiveDate::from_ymd_opt(2025, 3, 21).unwrap();
+        assert_eq!(Persian.to_calendar_date(nowruz), Some(CalendarDate::new(1404, 1, 1)));
+        assert_eq!(Persian.to_calendar_date(nowruz.pred_opt().unwrap()), Some(CalendarDate::new(1403, 12, 30)));
+        assert_eq!(Persian.to_date(CalendarDate::new(1404, 7, 1)), NaiveDate::from_ymd_opt(2025, 9, 23));
+        assert_eq!(Persian.to_date(CalendarDate::new(1404, 12, 30)), None);
+        assert_eq!(Persian.month_name(CalendarDate::new(1404, 7, 1)), "Mehr");
+    }
+    #[test]
+    fn test_persian_roundtrip() {
+        let mut date = NaiveDate::from_ymd_opt(700, 1, 1).unwrap();
+        while date.year() < 2100 {
+            assert_eq!(Persian.to_date(Persian.to_calendar_date(date).unwrap()), Some(date));
+            date += Duration::days(13);
+        }
+    }
+}
@@ -0,0 +1,416 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+//! Validation of the configuration file, the theme, the ics files and
+//! the templates, used by `carl check`.
+
+use crate::calendar::{calendar_system, Calendar};
+use crate::cli::Cli;
+use crate::config::{describe_toml_error, Config, DateStyle, HolidayStyle, IcalStyle, Location, Style, Theme};
+use crate::context::Context;
+use crate::error::Error;
+use crate::events::{holidays, Events, ReadFromIcsFile};
+use crate::template;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Check the whole setup of carl and return all the problems found.
+pub fn check(opts: Cli) -> Vec<Error> {
+    let mut problems: Vec<Error> = vec![];
+
+    // Check every configuration file on its own, including the files
+    // they include, before reading the combined configuration.
+    let mut config_files: Vec<PathBuf> = Config::files(&opts.config);
+    let mut index = 0;
+    while let Some(path) = config_files.get(index).cloned() {
+        index += 1;
+        let config_error = |message: String| Error::Config {
+            path: path.clone(),
+            message,
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                let (_, messages) = check_config(&content);
+                problems.extend(messages.into_iter().map(config_error));
+                if let Ok(table) = content.parse::<toml::Table>() {
+                    match Config::includes(&path, &table) {
+                        Ok(includes) => {
+                            for include in includes {
+                                if !config_files.contains(&include) {
+                                    config_files.push(include);
+                                }
+                            }
+                        }
+                        Err(message) => problems.push(config_error(message)),
+                    }
+                }
+            }
+            Err(e) => problems.push(config_error(e.to_string())),
+        }
+    }
+    let config = match Config::read(&opts.config) {
+        Ok(config) => config,
+        Err(e) => {
+            if problems.is_empty() {
+                problems.push(e);
+            }
+            Config::default()
+        }
+    };
+
+    // Check every themefile on its own, including the themes they
+    // extend, before reading the stacked theme.
+    let themenames = if opts.theme.is_empty() { &config.theme } else { &opts.theme };
+    let mut themes: Vec<String> = themenames.clone();
+    let mut index = 0;
+    while let Some(themename) = themes.get(index).cloned() {
+        index += 1;
+        let theme_error = |message: String| Error::Theme {
+            name: themename.clone(),
+            message,
+        };
+        match Theme::find(&themename) {
+            Ok(Some((origin, content))) => {
+                let (parsed, messages) = check_theme(&content);
+                problems.extend(messages.into_iter().map(|message| theme_error(format!("{}: {}", origin, message))));
+                for parent in parsed.map(|theme| theme.extends).unwrap_or_default() {
+                    let parent = Theme::parent(&origin, parent);
+                    if parent != themename && !themes.contains(&parent) {
+                        themes.push(parent);
+                    }
+                }
+            }
+            Ok(None) => problems.push(theme_error(String::from("no such theme"))),
+            Err(e) => problems.push(e),
+        }
+    }
+    let theme = match Theme::read(themenames, &mut vec![]) {
+        Ok(theme) => theme,
+        Err(e) => {
+            if problems.is_empty() {
+                problems.push(e);
+            }
+            Theme::default()
+        }
+    };
+
+    // Broken files of a directory are warnings when rendering, here
+    // they are problems
+    for icalstyle in &config.ical {
+        let mut warnings = vec![];
+        if let Err(e) = Events::read_from_ics_file(&icalstyle.file, &mut warnings) {
+            problems.push(e);
+        }
+        problems.append(&mut warnings);
+    }
+    for holidaystyle in &config.holidays {
+        if let Err(message) = holidays(&holidaystyle.country, holidaystyle.region.as_deref(), 2000) {
+            problems.push(Error::Setting {
+                name: String::from("holidays"),
+                message,
+            });
+        }
+    }
+
+    if let Some(Err(message)) = config.location.map(|location| location.validate()) {
+        problems.push(Error::Setting {
+            name: String::from("location"),
+            message,
+        });
+    }
+
+    if let Some(reform) = config.reform.as_deref().filter(|reform| Calendar::from_name(reform).is_none()) {
+        problems.push(Error::Setting {
+            name: String::from("reform"),
+            message: format!("unknown reform {}: use 1582, 1752, gregorian or julian", reform),
+        });
+    }
+
+    if let Some(name) = config.secondary_calendar.as_deref().filter(|name| calendar_system(name).is_none()) {
+        problems.push(Error::Setting {
+            name: String::from("secondary_calendar"),
+            message: format!("unknown calendar {}: use gregorian, julian, hebrew, islamic or persian", name),
+        });
+    }
+
+    if let Some(template_dir) = &config.template_dir {
+        if config.template().is_none() {
+            problems.push(Error::Setting {
+                name: String::from("template_dir"),
+                message: format!("{} is not a directory", template_dir),
+            });
+        }
+    }
+
+    // The background of the terminal does not matter for the check, so
+    // it is not detected
+    let opts = Cli {
+        themestyletype: Some(String::from("light")),
+        ..opts
+    };
+    match Context::from_parts(opts, config, theme) {
+        Ok(ctx) => {
+            if let Some(path) = ctx.config.template() {
+                let env = template::environment(&ctx);
+                for name in template_names(&path, &path) {
+                    if let Err(e) = env.get_template(&name) {
+                        problems.push(Error::Template(e));
+                    }
+                }
+            }
+            // Errors like unknown filters or functions only show up
+            // when rendering.
+            if problems.is_empty() {
+                if let Err(e) = template::render(&ctx) {
+                    problems.push(e);
+                }
+            }
+        }
+        Err(e) => problems.push(e),
+    }
+
+    problems
+}
+
+/// Check the content of a configuration file. Returns the parsed
+/// configuration (if it could be parsed) and the problems found.
+pub fn check_config(content: &str) -> (Option<Config>, Vec<String>) {
+    check_document(content, |table, messages| {
+        // The included files are checked on their own
+        table.remove("include");
+        check_entries::<IcalStyle>(table, "ical", messages);
+        check_entries::<HolidayStyle>(table, "holidays", messages);
+        check_table::<Style>(table, "astronomy", messages);
+        check_table::<Location>(table, "location", messages);
+    })
+}
+
+/// Check the content of a themefile. Returns the parsed theme (if it
+/// could be parsed) and the problems found.
+pub fn check_theme(content: &str) -> (Option<Theme>, Vec<String>) {
+    check_document(content, |table, messages| {
+        check_entries::<DateStyle>(table, "date", messages);
+        check_entries::<Style>(table, "weeknumber", messages);
+    })
+}
+
+// The tables and the lists of tables are checked one by one, so that
+// the problems of every entry are found. The unknown keys of the
+// document itself are only found if it can be parsed.
+fn check_document<T: DeserializeOwned + Serialize>(
+    content: &str,
+    check_tables: impl Fn(&mut toml::Table, &mut Vec<String>),
+) -> (Option<T>, Vec<String>) {
+    let mut messages: Vec<String> = vec![];
+    let mut table: toml::Table = match toml::from_str(content) {
+        Ok(table) => table,
+        Err(e) => return (None, vec![describe_toml_error(content, &e)]),
+    };
+    check_tables(&mut table, &mut messages);
+    match toml::from_str(content) {
+        Ok(parsed) => {
+            let mut unknown = unknown_keys(&table, &parsed, "");
+            unknown.append(&mut messages);
+            (Some(parsed), unknown)
+        }
+        Err(e) => {
+            // The errors in the entries of the tables are already
+            // reported, with more context
+            if messages.is_empty() {
+                messages.push(describe_toml_error(content, &e));
+            }
+            (None, messages)
+        }
+    }
+}
+
+fn check_entries<T: DeserializeOwned + Serialize>(table: &toml::Table, name: &str, messages: &mut Vec<String>) {
+    let Some(toml::Value::Array(entries)) = table.get(name) else {
+        return;
+    };
+    for (index, entry) in entries.iter().enumerate() {
+        check_entry::<T>(entry, &format!("[[{}]] #{}", name, index + 1), messages);
+    }
+}
+
+fn check_table<T: DeserializeOwned + Serialize>(table: &toml::Table, name: &str, messages: &mut Vec<String>) {
+    if let Some(entry) = table.get(name) {
+        check_entry::<T>(entry, &format!("[{}]", name), messages);
+    }
+}
+
+fn check_entry<T: DeserializeOwned + Serialize>(entry: &toml::Value, location: &str, messages: &mut Vec<String>) {
+    match (entry, entry.clone().try_into::<T>()) {
+        (toml::Value::Table(table), Ok(parsed)) => messages.extend(unknown_keys(table, &parsed, location)),
+        (_, Ok(_)) => {}
+        (_, Err(e)) => messages.push(format!("{}: {}", location, e.message())),
+    }
+}
+
+// Serde skips the keys it does not know (also those next to a
+// `flatten`ed struct, which `serde_ignored` and `deny_unknown_fields`
+// do not see). The keys of `table` that are missing when `parsed`, the
+// value deserialized from it, is serialized again were not used.
+fn unknown_keys<T: Serialize>(table: &toml::Table, parsed: &T, location: &str) -> Vec<String> {
+    let Ok(toml::Value::Table(known)) = toml::Value::try_from(parsed) else {
+        return vec![];
+    };
+    table
+        .keys()
+        .filter(|key| !known.contains_key(*key))
+        .map(|key| match location {
+            "" => format!("unknown key `{}`", key),
+            _ => format!("{}: unknown key `{}`", location, key),
+        })
+        .collect()
+}
+
+/// The names of all the template files in `dir`, relative to `base`.
+fn template_names(base: &Path, dir: &Path) -> Vec<String> {
+    let mut names = vec![];
+    let Ok(entries) = dir.read_dir() else {
+        return names;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            names.append(&mut template_names(base, &path));
+        } else if let Ok(name) = path.strip_prefix(base).map(PathBuf::from) {
+            names.push(name.to_string_lossy().to_string());
+        }
+    }
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_config_valid() {
+        let content = fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/data/config.toml")).unwrap();
+        let (config, messages) = check_config(&content);
+        assert!(config.is_some());
+        assert!(messages.is_empty());
+    }
+    #[test]
+    fn test_check_config_unknown_keys() {
+        let (config, messages) = check_config("themes = 'foo'\n[[ical]]\nfile = 'a.ics'\ncolor = 'red'\n");
+        assert!(config.is_some());
+        assert_eq!(messages, vec!["unknown key `themes`", "[[ical]] #1: unknown key `color`"]);
+    }
+    #[test]
+    fn test_check_config_all_keys() {
+        let content = "include = ['team.toml']
+theme = ['base', 'team.theme']
+template_dir = 'templates'
+styletype = 'auto'
+locale = 'de_AT'
+reform = '1752'
+secondary_calendar = 'hebrew'
+
+[[ical]]
+file = 'a.ics'
+name = 'work'
+filter = 'meeting'
+stylenames = ['Bold']
+weight = 2
+styletype = 'Dark'
+
+[[holidays]]
+country = 'DE'
+region = 'BY'
+stylenames = ['FGRed']
+
+[astronomy]
+stylenames = ['FGYellow']
+
+[location]
+lat = 48.2
+lon = 16.37
+";
+        let (config, messages) = check_config(content);
+        assert!(config.is_some());
+        assert!(messages.is_empty(), "{:?}", messages);
+    }
+    #[test]
+    fn test_check_config_unknown_keys_next_to_style() {
+        let (_, messages) = check_config("[[holidays]]\ncountry = 'AT'\nstylenames = ['FGRed']\ncolour = 'red'\n");
+        assert_eq!(messages, vec!["[[holidays]] #1: unknown key `colour`"]);
+        let (_, messages) = check_theme("[[date]]\nproperties = ['Today']\nstylename = ['Bold']\n");
+        assert_eq!(messages, vec!["[[date]] #1: unknown key `stylename`"]);
+    }
+    #[test]
+    fn test_check_config_table() {
+        let (config, messages) = check_config("[astronomy]
+stylenames = ['FGYellow']
+color = 'red'
+");
+        assert!(config.is_some());
+        assert_eq!(messages, vec!["[astronomy]: unknown key `color`"]);
+    }
+    #[test]
+    fn test_check_config_syntax_error() {
+        let (config, messages) = check_config("theme = 'foo'\nical = [\n");
+        assert!(config.is_none());
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].starts_with("line 2: "));
+    }
+    #[test]
+    fn test_check_theme_valid() {
+        let content = fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/themes/default.theme")).unwrap();
+        let (theme, messages) = check_theme(&content);
+        assert!(theme.is_some());
+        assert!(messages.is_empty());
+    }
+    #[test]
+    fn test_check_theme_all_keys() {
+        let content = "extends = 'base'
+
+[[date]]
+properties = [{ NthWeekday = { n = -1, weekday = 'Sat' } }, { DateRange = { from = '2025-12-24', to = '2025-12-26' } }]
+stylenames = [{ FGRGB = { r = 1, g = 2, b = 3 } }]
+weight = 1
+styletype = 'Light'
+
+[[weeknumber]]
+stylenames = ['Dimmed']
+";
+        let (theme, messages) = check_theme(content);
+        assert!(theme.is_some());
+        assert!(messages.is_empty(), "{:?}", messages);
+    }
+    #[test]
+    fn test_check_theme_unknown_values() {
+        let content = "[[date]]\nproperties = ['Today']\n\n[[date]]\nproperties = ['Tomorrow']\n\n[[weeknumber]]\nstylenames = ['FGPink']\n";
+        let (theme, messages) = check_theme(content);
+        assert!(theme.is_none());
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].starts_with("[[date]] #2: unknown variant `Tomorrow`"));
+        assert!(messages[1].starts_with("[[weeknumber]] #1: unknown variant `FGPink`"));
+    }
+    #[test]
+    fn test_check_ics_directory() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = tempdir.path();
+        fs::create_dir(dir.join("calendars")).unwrap();
+        fs::write(dir.join("calendars/broken.ics"), "BEGIN:VCALENDAR\nfoo").unwrap();
+        fs::write(dir.join("config.toml"), format!("[[ical]]\nfile = '{}'\n", dir.join("calendars").display())).unwrap();
+        let opts = Cli {
+            config: Some(dir.join("config.toml")),
+            ..Default::default()
+        };
+        let problems = check(opts);
+        assert_eq!(problems.len(), 1);
+        assert!(matches!(&problems[0], Error::Ics { path, .. } if path.ends_with("broken.ics")));
+    }
+    #[test]
+    fn test_template_names() {
+        let dir = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/templates"));
+        let names = template_names(dir, dir);
+        assert!(names.contains(&String::from("carl.tmpl")));
+    }
+}
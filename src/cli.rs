@@ -2,11 +2,15 @@
 //
 // SPDX-License-Identifier: MIT
 
-use chrono::prelude::*;
-use clap::{crate_authors, crate_name, crate_version, Args, Parser};
+use crate::calendar::Calendar;
+use crate::utils::{parse_date, ParsedDate};
+use clap::{crate_authors, crate_version, Args, Parser, Subcommand};
 use serde::{Deserialize, Serialize};
 
-#[derive(Parser, Serialize, Deserialize, Debug)]
+/// The maximum number of months `-A` and `-B` add, a hundred years.
+const MAX_MONTHS: i64 = 1200;
+
+#[derive(Parser, Clone, Serialize, Deserialize, Debug, Default)]
 #[clap(version = crate_version!(), author = crate_authors!(","), about = "Display a calendar")]
 pub struct Cli {
     #[clap(short = '1', long = "one",
@@ -29,29 +33,120 @@ pub struct Cli {
 
     #[clap(short = 'y', long = "year",
            help = "show whole current year",
-           conflicts_with_all = &["one", "three", "months"])]
+           conflicts_with_all = &["one", "three", "months", "after", "before"])]
     pub year: bool,
 
-    #[clap(short = 's', long = "sunday", help = "Sunday as first day of week")]
+    #[clap(short = 'A', long = "after",
+           value_name = "NUMBER", value_parser = clap::value_parser!(u32).range(..=MAX_MONTHS),
+           help = "show NUMBER months after the current month")]
+    pub after: Option<u32>,
+    #[clap(short = 'B', long = "before",
+           value_name = "NUMBER", value_parser = clap::value_parser!(u32).range(..=MAX_MONTHS),
+           help = "show NUMBER months before the current month")]
+    pub before: Option<u32>,
+
+    #[clap(long = "from",
+           value_name = "YYYY-MM-DD",
+           help = "show the dates starting with the given date",
+           conflicts_with_all = &["one", "three", "year", "months", "after", "before"])]
+    pub from: Option<chrono::NaiveDate>,
+    #[clap(long = "to",
+           value_name = "YYYY-MM-DD",
+           help = "show the dates up to and including the given date",
+           conflicts_with_all = &["one", "three", "year", "months", "after", "before"])]
+    pub to: Option<chrono::NaiveDate>,
+
+    #[clap(short = 's', long = "sunday", help = "Sunday as first day of week (default depends on the locale)",
+           conflicts_with = "monday")]
     pub sunday: bool,
-    #[clap(short = 'm', long = "monday", help = "Monday as first day of week")]
+    #[clap(short = 'm', long = "monday", help = "Monday as first day of week (default depends on the locale)")]
     pub monday: bool,
 
     #[clap(short = 'j', long = "julian", help = "output Julian dates")]
     pub julian: bool,
 
+    #[clap(long = "reform", value_name = "REFORM",
+           help = "use the julian calendar before the gregorian reform of the given year, or only one of the calendars",
+           value_parser = ["1582", "1752", "gregorian", "julian"])]
+    pub reform: Option<String>,
+
+    #[clap(long = "secondary-calendar", value_name = "CALENDAR",
+           help = "show the dates in the agenda and the day view also in another calendar",
+           value_parser = ["gregorian", "julian", "hebrew", "islamic", "persian"])]
+    pub secondary_calendar: Option<String>,
+
+    #[clap(short = 'w', long = "week-numbers",
+           help = "show week numbers (ISO weeks, US weeks with --sunday)")]
+    pub week_numbers: bool,
+
     #[clap(long = "themestyletype",
-           help = "select dark or light theme styles",
-           value_parser=["dark", "light"])]
+           help = "select dark or light theme styles, or detect them from the terminal background",
+           value_parser=["dark", "light", "auto"])]
     pub themestyletype: Option<String>,
-    #[clap(long = "theme", help = "select theme by name", num_args(1))]
-    pub theme: Option<String>,
+    #[clap(long = "theme", value_name = "THEME",
+           help = "select theme by name or by the path of a themefile, can be given multiple times to stack themes", num_args(1))]
+    pub theme: Vec<String>,
+    #[clap(long = "list-themes", help = "list the available themes")]
+    pub list_themes: bool,
+
+    #[clap(long = "config", value_name = "FILE", env = "CARL_CONFIG",
+           help = "read the configuration from FILE instead of the default location")]
+    pub config: Option<std::path::PathBuf>,
+
+    #[clap(long = "today", value_name = "YYYY-MM-DD", env = "CARL_TODAY",
+           help = "use the given date as today instead of the current date")]
+    pub today: Option<chrono::NaiveDate>,
+
+    #[clap(long = "strict", help = "treat warnings as errors")]
+    pub strict: bool,
+
+    #[clap(long = "interactive",
+           help = "browse the calendar interactively, with the agenda of the selected date",
+           conflicts_with_all = &["from", "to"])]
+    pub interactive: bool,
 
-    #[clap(num_args(0..=3))]
+    #[clap(long = "watch",
+           help = "render again when the configuration, the theme, the templates, an ics file or the date changes",
+           conflicts_with = "interactive")]
+    pub watch: bool,
+
+    #[clap(long = "bar", value_name = "FORMAT",
+           help = "show the next event for a status bar: waybar (JSON with the calendar as tooltip), i3bar or polybar",
+           value_parser = ["waybar", "i3bar", "polybar"],
+           conflicts_with_all = &["interactive", "watch"])]
+    pub bar: Option<String>,
+
+    #[clap(long = "grep", value_name = "PATTERN",
+           help = "use only the events whose summary contains PATTERN, ignoring the case, can be given multiple times", num_args(1))]
+    pub grep: Vec<String>,
+    #[clap(long = "ical", value_name = "NAME",
+           help = "use only the events of the ical entries with the given name (the filename without extension by default), can be given multiple times", num_args(1))]
+    pub ical: Vec<String>,
+    #[clap(long = "category", value_name = "CATEGORY",
+           help = "use only the events with the given category, ignoring the case, can be given multiple times", num_args(1))]
+    pub category: Vec<String>,
+
+    #[clap(num_args(0..=3),
+           help = "the date to show, e.g. `2026 10 17`, `2026-10-17`, `oct 2026`, `tomorrow`, `next month` or `+2w`")]
     pub date: Vec<String>,
 
     #[command(flatten)]
     pub action: Action,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub enum Command {
+    /// validate the configuration, the theme, the templates and the ics files
+    Check,
+    /// show the next events and the time until they start
+    Upcoming {
+        /// the number of events to show
+        #[clap(default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+        count: u16,
+    },
 }
 
 #[derive(Args, Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
@@ -63,126 +158,60 @@ pub struct Action {
     pub agenda: bool,
     #[clap(long = "year-progress", help = "show year progress")]
     pub yearprogress: bool,
+    #[clap(long = "daylight", help = "show sunrise, sunset and day length of the configured location")]
+    pub daylight: bool,
+    #[clap(long = "week",
+           help = "show the week of the date with hourly slots",
+           conflicts_with_all = &["one", "three", "year", "months", "after", "before", "from", "to", "day"])]
+    pub week: bool,
+    #[clap(long = "day",
+           help = "show the date with hourly slots",
+           conflicts_with_all = &["one", "three", "year", "months", "after", "before", "from", "to", "week"])]
+    pub day: bool,
 }
 
 impl Cli {
-    pub fn validate_date(&self) -> Result<chrono::NaiveDate, String> {
-        let mut today: chrono::NaiveDate = Local::now().date_naive();
-        let mut year: i32 = today.year();
-        let mut month: u32 = today.month();
-        let mut day: u32 = today.day();
-
-        if !self.date.is_empty() {
-            year = match self.date[0].parse() {
-                Ok(x) => {
-                    if x > 9999 {
-                        return Err(format!(
-                            "{}: illegal year value: use 1-9999: {}",
-                            crate_name!(),
-                            x
-                        ));
-                    }
-                    x
-                }
-                Err(x) => {
-                    return Err(format!(
-                        "{}: illegal year value: use 1-9999: {}",
-                        crate_name!(),
-                        x
-                    ));
-                }
-            }
-        }
-        if self.date.len() >= 2 {
-            month = match self.date[1].parse() {
-                Ok(x) => {
-                    if x > 12 {
-                        return Err(format!(
-                            "{}: illegal month value: use 1-12: {}",
-                            crate_name!(),
-                            x
-                        ));
-                    }
-                    x
-                }
-                Err(x) => {
-                    return Err(format!(
-                        "{}: illegal month value: use 1-12: {}",
-                        crate_name!(),
-                        x
-                    ));
-                }
-            }
-        }
-        if self.date.len() == 3 {
-            // TODO: this should depend on the days a month has
-            day = match self.date[2].parse() {
-                Ok(x) => {
-                    if x > 31 {
-                        return Err(format!(
-                            "{}: illegal day value: use 1-12: {}",
-                            crate_name!(),
-                            x
-                        ));
-                    }
-                    x
-                }
-                Err(x) => {
-                    return Err(format!(
-                        "{}: illegal day value: use 1-12: {}",
-                        crate_name!(),
-                        x
-                    ));
-                }
-            }
-        }
-        if self.date.len() > 3 {
-            eprintln!("Could not parse date value(s) - using today.");
-            return Ok(today);
-        }
-        //if let Some(x) = Local.ymd_opt(year, month, day).single() {
-        if let Some(x) = NaiveDate::from_ymd_opt(year, month, day) {
-            today = x;
-        } else {
-            eprintln!("Could not parse date value(s) - using today.");
-        }
-        Ok(today)
+    pub fn validate_date(&self, today: chrono::NaiveDate, calendar: &Calendar) -> Result<ParsedDate, String> {
+        parse_date(&self.date, today, calendar)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::prelude::*;
+
+    fn fixed_today() -> chrono::NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 9, 9).unwrap()
+    }
 
     #[test]
     fn test_validate_date_defaults_to_now() {
-        let today: chrono::NaiveDate = Local::now().date_naive();
+        let today: chrono::NaiveDate = fixed_today();
         let o: Cli = Cli::parse();
-        assert_eq!(today, o.validate_date().unwrap());
+        assert_eq!(today, o.validate_date(fixed_today(), &Calendar::Gregorian).unwrap().date);
     }
     #[test]
     fn test_validate_date_default_to_now_with_custom_year() {
-        let today: chrono::NaiveDate = Local::now().date_naive().with_year(2007).unwrap();
+        let today: chrono::NaiveDate = fixed_today().with_year(2007).unwrap();
         let mut o: Cli = Cli::parse();
         o.date = vec![String::from("2007")];
-        assert_eq!(today, o.validate_date().unwrap());
+        assert_eq!(today, o.validate_date(fixed_today(), &Calendar::Gregorian).unwrap().date);
     }
     #[test]
     fn test_validate_date_defaults_to_now_with_custom_year_and_month() {
-        let today: chrono::NaiveDate = Local::now()
-            .date_naive()
+        let today: chrono::NaiveDate = fixed_today()
             .with_year(2007)
             .unwrap()
             .with_month(1)
             .unwrap();
         let mut o: Cli = Cli::parse();
         o.date = vec![String::from("2007"), String::from("1")];
-        assert_eq!(today, o.validate_date().unwrap());
+        assert_eq!(today, o.validate_date(fixed_today(), &Calendar::Gregorian).unwrap().date);
     }
     #[test]
     fn test_validate_date_defaults_to_now_with_custom_year_and_month_and_day() {
-        let today: chrono::NaiveDate = Local::now()
-            .date_naive()
+        let today: chrono::NaiveDate = fixed_today()
             .with_year(2007)
             .unwrap()
             .with_month(1)
@@ -191,11 +220,10 @@ mod tests {
             .unwrap();
         let mut o: Cli = Cli::parse();
         o.date = vec![String::from("2007"), String::from("1"), String::from("28")];
-        assert_eq!(today, o.validate_date().unwrap());
+        assert_eq!(today, o.validate_date(fixed_today(), &Calendar::Gregorian).unwrap().date);
     }
     #[test]
-    fn test_validate_date_defaults_to_now_with_ambiguous_arguments() {
-        let today: chrono::NaiveDate = Local::now().date_naive();
+    fn test_validate_date_errors_with_ambiguous_arguments() {
         let mut o: Cli = Cli::parse();
         o.date = vec![
             String::from("2007"),
@@ -203,19 +231,19 @@ mod tests {
             String::from("28"),
             String::from("28"),
         ];
-        assert_eq!(today, o.validate_date().unwrap());
+        assert!(o.validate_date(fixed_today(), &Calendar::Gregorian).is_err());
     }
     #[test]
     fn test_validate_date_errors_with_wrong_month() {
         let mut o: Cli = Cli::parse();
         o.date = vec![String::from("2007"), String::from("13"), String::from("28")];
-        assert!(o.validate_date().is_err());
+        assert!(o.validate_date(fixed_today(), &Calendar::Gregorian).is_err());
     }
     #[test]
     fn test_validate_date_errors_with_wrong_day() {
         let mut o: Cli = Cli::parse();
         o.date = vec![String::from("2007"), String::from("11"), String::from("33")];
-        assert!(o.validate_date().is_err());
+        assert!(o.validate_date(fixed_today(), &Calendar::Gregorian).is_err());
     }
     #[test]
     fn test_validate_date_errors_with_wrong_year() {
@@ -225,13 +253,13 @@ mod tests {
             String::from("11"),
             String::from("28"),
         ];
-        assert!(o.validate_date().is_err());
+        assert!(o.validate_date(fixed_today(), &Calendar::Gregorian).is_err());
     }
     #[test]
     fn test_validate_date_errors_with_unparsable_year() {
         let mut o: Cli = Cli::parse();
         o.date = vec![String::from("foo"), String::from("13"), String::from("28")];
-        assert!(o.validate_date().is_err());
+        assert!(o.validate_date(fixed_today(), &Calendar::Gregorian).is_err());
     }
     #[test]
     fn test_validate_date_errors_with_unparsable_month() {
@@ -241,7 +269,7 @@ mod tests {
             String::from("foo"),
             String::from("23"),
         ];
-        assert!(o.validate_date().is_err());
+        assert!(o.validate_date(fixed_today(), &Calendar::Gregorian).is_err());
     }
     #[test]
     fn test_validate_date_errors_with_unparsable_day() {
@@ -251,13 +279,62 @@ mod tests {
             String::from("11"),
             String::from("foo"),
         ];
-        assert!(o.validate_date().is_err());
+        assert!(o.validate_date(fixed_today(), &Calendar::Gregorian).is_err());
     }
     #[test]
     fn test_validate_date_errors_with_non_existent_date() {
-        let today: chrono::NaiveDate = Local::now().date_naive();
         let mut o: Cli = Cli::parse();
         o.date = vec![String::from("2007"), String::from("2"), String::from("30")];
-        assert_eq!(today, o.validate_date().unwrap());
+        assert!(o.validate_date(fixed_today(), &Calendar::Gregorian).is_err());
+    }
+    #[test]
+    fn test_today_option() {
+        let o: Cli = Cli::parse_from(["carl", "--today", "2007-01-28"]);
+        assert_eq!(o.today, NaiveDate::from_ymd_opt(2007, 1, 28));
+    }
+    #[test]
+    fn test_after_before_limit() {
+        let o: Cli = Cli::parse_from(["carl", "-B", "1200", "-A", "1200"]);
+        assert_eq!((o.before, o.after), (Some(1200), Some(1200)));
+        assert!(Cli::try_parse_from(["carl", "-A", "4000000000"]).is_err());
+        assert!(Cli::try_parse_from(["carl", "-B", "1201"]).is_err());
+    }
+    #[test]
+    fn test_validate_date_with_iso_date() {
+        let mut o: Cli = Cli::parse();
+        o.date = vec![String::from("2007-01-28")];
+        assert_eq!(o.validate_date(fixed_today(), &Calendar::Gregorian).unwrap().date, NaiveDate::from_ymd_opt(2007, 1, 28).unwrap());
+    }
+    #[test]
+    fn test_upcoming_command() {
+        let o: Cli = Cli::parse_from(["carl", "upcoming", "3"]);
+        assert_eq!(o.command, Some(Command::Upcoming { count: 3 }));
+        let o: Cli = Cli::parse_from(["carl", "upcoming"]);
+        assert_eq!(o.command, Some(Command::Upcoming { count: 1 }));
+    }
+    #[test]
+    fn test_next_relative_date() {
+        for unit in ["day", "week", "month", "year"] {
+            let o: Cli = Cli::parse_from(["carl", "next", unit, "-a"]);
+            assert_eq!(o.command, None);
+            assert_eq!(o.date, vec![String::from("next"), String::from(unit)]);
+            assert!(o.action.agenda);
+        }
+        let o: Cli = Cli::parse_from(["carl", "--grep", "next", "week"]);
+        assert_eq!(o.grep, vec![String::from("next")]);
+        assert_eq!(o.date, vec![String::from("week")]);
+    }
+    #[test]
+    fn test_secondary_calendar_option() {
+        let o: Cli = Cli::parse_from(["carl", "--secondary-calendar", "hebrew"]);
+        assert_eq!(o.secondary_calendar.as_deref(), Some("hebrew"));
+        assert!(Cli::try_parse_from(["carl", "--secondary-calendar", "mayan"]).is_err());
+    }
+    #[test]
+    fn test_reform_option() {
+        let o: Cli = Cli::parse_from(["carl", "--reform", "1752", "1752", "9", "2"]);
+        assert_eq!(o.reform.as_deref(), Some("1752"));
+        assert_eq!(o.validate_date(fixed_today(), &Calendar::REFORM_1752).unwrap().date, NaiveDate::from_ymd_opt(1752, 9, 13).unwrap());
+        assert!(Cli::try_parse_from(["carl", "--reform", "1600"]).is_err());
     }
 }
@@ -5,57 +5,281 @@
 mod theme;
 
 pub use theme::StyleName::*;
-pub use theme::{DateProperty, Style, StyleName, StyleType, Theme};
+pub use theme::{DateProperty, DateStyle, Parity, Style, StyleName, StyleType, Theme};
 
+use crate::error::Error;
 use clap::crate_name;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Deserialize, Serialize, Debug, Default)]
 #[serde(default)]
 pub struct Config {
-    pub theme: Option<String>,
+    #[serde(deserialize_with = "string_or_list")]
+    pub theme: Vec<String>,
     pub ical: Vec<IcalStyle>,
+    pub holidays: Vec<HolidayStyle>,
+    pub astronomy: Option<Style>,
+    pub location: Option<Location>,
     pub template_dir: Option<String>,
+    pub styletype: Option<StyleTypeSetting>,
+    pub locale: Option<String>,
+    pub reform: Option<String>,
+    pub secondary_calendar: Option<String>,
+}
+
+/// Which styles of a theme are used: those for a dark or those for a
+/// light background, or the ones matching the background of the
+/// terminal.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub enum StyleTypeSetting {
+    #[serde(alias = "dark")]
+    Dark,
+    #[serde(alias = "light")]
+    Light,
+    #[serde(alias = "auto")]
+    Auto,
 }
 
 impl Config {
+    /// Read the configuration. The system wide configuration files are
+    /// read first, then the configuration file of the user or, if given,
+    /// `config_file`. Settings of later files override the settings of
+    /// earlier files, except for lists like `[[ical]]`, which are combined.
     #[cfg(not(tarpaulin_include))]
-    pub fn read() -> Config {
-        if let Some(config_path) = directories::ProjectDirs::from("org", "bisco", crate_name!()) {
-            let config_path = config_path.config_dir().as_os_str();
-            let config_file = PathBuf::from(config_path).join("config.toml");
-            if config_file.exists() {
-                let config_content = fs::read_to_string(config_file).unwrap_or_default();
-                match toml::from_str(&config_content) {
-                    Ok(config) => return config,
-                    Err(e) => eprintln!("Could not parse config file: {}", e),
-                }
-            }
-        } else {
-            //for now disabled, should only be shown with some kind of --debug flag
-            //eprintln!("Could not load configuration file, using default settings.");
+    pub fn read(config_file: &Option<PathBuf>) -> Result<Config, Error> {
+        let mut table = toml::Table::new();
+        let mut seen: Vec<PathBuf> = vec![];
+        for path in Config::files(config_file) {
+            merge_tables(&mut table, read_table(&path, &mut vec![], &mut seen)?);
+        }
+        toml::Value::Table(table).try_into().map_err(|e| Error::Config {
+            path: config_file.clone().or_else(Config::path).unwrap_or_default(),
+            message: e.message().to_string(),
+        })
+    }
+
+    /// The configuration files that are read, the least important first.
+    /// Files that are included by those files are not listed.
+    pub fn files(config_file: &Option<PathBuf>) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = system_config_dirs()
+            .iter()
+            .rev()
+            .map(|dir| dir.join(crate_name!()).join("config.toml"))
+            .filter(|path| path.exists())
+            .collect();
+        match config_file {
+            Some(path) => files.push(path.clone()),
+            None => files.extend(Config::path().filter(|path| path.exists())),
         }
-        Config::default()
+        files
+    }
+
+    /// The location of the configuration file of the user, if a
+    /// configuration directory could be determined.
+    pub fn path() -> Option<PathBuf> {
+        let project_dirs = directories::ProjectDirs::from("org", "bisco", crate_name!())?;
+        Some(project_dirs.config_dir().join("config.toml"))
+    }
+
+    /// Read and parse the configuration file `path`, including the files
+    /// it includes.
+    pub fn read_file(path: &Path) -> Result<Config, Error> {
+        let table = read_table(path, &mut vec![], &mut vec![])?;
+        toml::Value::Table(table).try_into().map_err(|e| Error::Config {
+            path: path.to_path_buf(),
+            message: e.message().to_string(),
+        })
+    }
+
+    /// The files included by the configuration file `path` using the
+    /// `include` setting. Relative paths are relative to the directory
+    /// of `path`.
+    pub fn includes(path: &Path, table: &toml::Table) -> Result<Vec<PathBuf>, String> {
+        let Some(include) = table.get("include") else {
+            return Ok(vec![]);
+        };
+        let files: Option<Vec<&str>> = match include {
+            toml::Value::Array(files) => files.iter().map(|file| file.as_str()).collect(),
+            _ => None,
+        };
+        let directory = path.parent().unwrap_or(Path::new(""));
+        files
+            .map(|files| files.iter().map(|file| directory.join(file)).collect())
+            .ok_or(String::from("`include` has to be a list of files"))
     }
 
-    pub fn template(self) -> Option<PathBuf> {
-        let template_file = self.template_dir?;
+    /// The template directory, if one is configured and it exists.
+    pub fn template(&self) -> Option<PathBuf> {
+        let template_file = self.template_dir.as_ref()?;
         let path = PathBuf::from(&template_file);
-        if path.is_dir() {
-            return Some(path);
-        } else {
-            eprintln!("Template directory: {} is not a directory.", path.display());
+        path.is_dir().then_some(path)
+    }
+}
+
+// Read the configuration file `path` and the files it includes. The
+// included files are merged in the order they are listed, the settings
+// of `path` itself override them. `stack` contains the files that are
+// currently being included, `seen` all the files that were read.
+fn read_table(path: &Path, stack: &mut Vec<PathBuf>, seen: &mut Vec<PathBuf>) -> Result<toml::Table, Error> {
+    let config_error = |message: String| Error::Config {
+        path: path.to_path_buf(),
+        message,
+    };
+    let content = fs::read_to_string(path).map_err(|e| config_error(e.to_string()))?;
+    let mut table: toml::Table = toml::from_str(&content).map_err(|e| config_error(describe_toml_error(&content, &e)))?;
+    let includes = Config::includes(path, &table).map_err(config_error)?;
+    table.remove("include");
+    // Validate every file on its own, so errors point to the right file
+    toml::Value::Table(table.clone())
+        .try_into::<Config>()
+        .map_err(|e| config_error(e.message().to_string()))?;
+
+    let canonical = path.canonicalize().unwrap_or(path.to_path_buf());
+    stack.push(canonical.clone());
+    seen.push(canonical);
+    let mut merged = toml::Table::new();
+    for include in includes {
+        let include_canonical = include.canonicalize().unwrap_or(include.clone());
+        if stack.contains(&include_canonical) {
+            return Err(config_error(format!("{} is included recursively", include.display())));
+        }
+        if !seen.contains(&include_canonical) {
+            let mut included = read_table(&include, stack, seen)?;
+            rebase_paths(&mut included, include_canonical.parent().unwrap_or(Path::new("")));
+            merge_tables(&mut merged, included);
+        }
+    }
+    stack.pop();
+    merge_tables(&mut merged, table);
+    Ok(merged)
+}
+
+// Make the relative paths of the `file` of the `[[ical]]` entries, of
+// `template_dir` and of the themes that are themefiles in `table`
+// relative to `directory`, the directory of the included file they were
+// read from. Paths that were already made absolute by a nested include
+// are left alone.
+fn rebase_paths(table: &mut toml::Table, directory: &Path) {
+    let rebase = |value: &mut toml::Value| {
+        let relative = value.as_str().map(Path::new).filter(|path| path.is_relative());
+        if let Some(path) = relative.map(|path| directory.join(path)) {
+            *value = toml::Value::String(path.display().to_string());
+        }
+    };
+    // Names of themes are looked up in the theme directories instead
+    let rebase_theme = |value: &mut toml::Value| {
+        if value.as_str().is_some_and(theme::is_path) {
+            rebase(value);
+        }
+    };
+    if let Some(value) = table.get_mut("template_dir") {
+        rebase(value);
+    }
+    match table.get_mut("theme") {
+        Some(value @ toml::Value::String(_)) => rebase_theme(value),
+        Some(toml::Value::Array(themes)) => themes.iter_mut().for_each(rebase_theme),
+        _ => {}
+    }
+    if let Some(toml::Value::Array(entries)) = table.get_mut("ical") {
+        for value in entries.iter_mut().filter_map(toml::Value::as_table_mut).filter_map(|entry| entry.get_mut("file")) {
+            rebase(value);
+        }
+    }
+}
+
+// Merge `overlay` into `base`: tables are merged, lists of tables are
+// combined and all other values of `overlay` replace those of `base`.
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => merge_tables(base, overlay),
+            (Some(toml::Value::Array(base)), toml::Value::Array(overlay))
+                if base.iter().chain(overlay.iter()).all(toml::Value::is_table) =>
+            {
+                base.extend(overlay)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+// The system wide configuration directories, the most important first
+fn system_config_dirs() -> Vec<PathBuf> {
+    let dirs = std::env::var("XDG_CONFIG_DIRS").unwrap_or_default();
+    let dirs: Vec<PathBuf> = dirs.split(':').filter(|dir| !dir.is_empty()).map(PathBuf::from).collect();
+    if dirs.is_empty() {
+        vec![PathBuf::from("/etc/xdg")]
+    } else {
+        dirs
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrList {
+    String(String),
+    List(Vec<String>),
+}
+
+/// Deserialize a setting that is either a single string or a list of
+/// strings, like `theme = "default"` or `theme = ["base", "holidays"]`.
+pub(crate) fn string_or_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(match StringOrList::deserialize(deserializer)? {
+        StringOrList::String(string) => vec![string],
+        StringOrList::List(list) => list,
+    })
+}
+
+/// A description of a toml parse error, including the line number.
+pub(crate) fn describe_toml_error(content: &str, e: &toml::de::Error) -> String {
+    match e.span() {
+        Some(span) => format!("line {}: {}", content[..span.start].lines().count().max(1), e.message()),
+        None => e.message().to_string(),
+    }
+}
+
+/// A location on earth, with the latitude and the longitude in degrees
+/// (north and east are positive).
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Location {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl Location {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(-90.0..=90.0).contains(&self.lat) {
+            return Err(format!("latitude {} is not between -90 and 90", self.lat));
+        }
+        if !(-180.0..=180.0).contains(&self.lon) {
+            return Err(format!("longitude {} is not between -180 and 180", self.lon));
         }
-        None
+        Ok(())
     }
 }
 
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(default)]
+pub struct HolidayStyle {
+    pub country: String,
+    pub region: Option<String>,
+    #[serde(flatten)]
+    pub style: Style,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(default)]
 pub struct IcalStyle {
     pub file: String,
+    /// The name used to select the events with `--ical`, defaults to
+    /// the name of the file without its extension.
+    pub name: Option<String>,
+    /// Only the events whose summary contains this text are used.
+    pub filter: Option<String>,
     #[serde(flatten)]
     pub style: Style,
 }
@@ -63,10 +287,23 @@ impl Default for IcalStyle {
     fn default() -> Self {
         IcalStyle {
             file: String::new(),
+            name: None,
+            filter: None,
             style: default_icalstyle_style(),
         }
     }
 }
+impl IcalStyle {
+    /// The configured name or the name of the file.
+    pub fn name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| {
+            Path::new(&self.file)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+    }
+}
 fn default_icalstyle_style() -> Style {
     Style {
         stylenames: vec![Underline, FGCyan],
@@ -82,15 +319,141 @@ mod tests {
     #[test]
     fn test_default_config() {
         let a = Config::default();
-        assert![a.theme.is_none()];
+        assert![a.theme.is_empty()];
     }
 
     #[test]
     fn test_config_read() {
         env::set_var("XDG_CONFIG_DIRS", "/nonexistent");
         env::set_var("XDG_CONFIG_HOME", "/nonexistent");
-        let a = Config::read();
-        assert![a.theme.is_none()];
+        let a = Config::read(&None).unwrap();
+        assert![a.theme.is_empty()];
+    }
+
+    #[test]
+    fn test_config_read_file_invalid() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/data/carl.ics");
+        let e = Config::read_file(Path::new(path)).unwrap_err();
+        assert![matches!(e, Error::Config { .. })];
+        assert![e.to_string().contains("carl.ics")];
+    }
+
+    #[test]
+    fn test_config_read_file_missing() {
+        assert![Config::read_file(Path::new("/nonexistent/config.toml")).is_err()];
+    }
+
+    #[test]
+    fn test_config_theme_list() {
+        let a: Config = toml::from_str("theme = 'default'").unwrap();
+        assert_eq![a.theme, vec!["default"]];
+        let a: Config = toml::from_str("theme = ['base', 'holidays']").unwrap();
+        assert_eq![a.theme, vec!["base", "holidays"]];
+    }
+
+    #[test]
+    fn test_config_styletype() {
+        let a: Config = toml::from_str("styletype = 'Auto'").unwrap();
+        assert_eq![a.styletype, Some(StyleTypeSetting::Auto)];
+        let a: Config = toml::from_str("styletype = 'dark'").unwrap();
+        assert_eq![a.styletype, Some(StyleTypeSetting::Dark)];
+        assert![toml::from_str::<Config>("styletype = 'None'").is_err()];
+    }
+
+    #[test]
+    fn test_merge_tables() {
+        let mut base: toml::Table = toml::from_str("theme = 'a'\n[[ical]]\nfile = 'a.ics'\n").unwrap();
+        let overlay: toml::Table = toml::from_str("theme = 'b'\n[[ical]]\nfile = 'b.ics'\n").unwrap();
+        merge_tables(&mut base, overlay);
+        let config: Config = toml::Value::Table(base).try_into().unwrap();
+        assert_eq![config.theme, vec!["b"]];
+        assert_eq![config.ical.len(), 2];
+    }
+
+    #[test]
+    fn test_config_read_file_include() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = tempdir.path();
+        fs::create_dir_all(dir.join("team")).unwrap();
+        fs::write(dir.join("team/shared.toml"), "theme = 'team'\ntemplate_dir = '/tmp'\n[[ical]]\nfile = 'team.ics'\n").unwrap();
+        fs::write(dir.join("config.toml"), "include = ['team/shared.toml']\ntheme = 'mine'\n[[ical]]\nfile = 'mine.ics'\n").unwrap();
+        let config = Config::read_file(&dir.join("config.toml")).unwrap();
+        assert_eq![config.theme, vec!["mine"]];
+        assert_eq![config.template_dir, Some(String::from("/tmp"))];
+        let team_ics = dir.canonicalize().unwrap().join("team").join("team.ics").display().to_string();
+        assert_eq![config.ical.iter().map(|i| i.file.as_str()).collect::<Vec<&str>>(), vec![team_ics.as_str(), "mine.ics"]];
+    }
+
+    #[test]
+    fn test_config_read_file_include_relative_paths() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = tempdir.path();
+        fs::create_dir_all(dir.join("team/calendars")).unwrap();
+        fs::write(dir.join("team/shared.toml"), "include = ['calendars/calendars.toml']\ntemplate_dir = 'templates'\n").unwrap();
+        fs::write(dir.join("team/calendars/calendars.toml"), "[[ical]]\nfile = 'team.ics'\n[[ical]]\nfile = '/srv/absolute.ics'\n").unwrap();
+        fs::write(dir.join("config.toml"), "include = ['team/shared.toml']\n").unwrap();
+        let config = Config::read_file(&dir.join("config.toml")).unwrap();
+        let team = dir.canonicalize().unwrap().join("team");
+        assert_eq![config.template_dir, Some(team.join("templates").display().to_string())];
+        assert_eq![config.ical[0].file, team.join("calendars").join("team.ics").display().to_string()];
+        assert_eq![config.ical[1].file, "/srv/absolute.ics"];
+    }
+
+    #[test]
+    fn test_config_read_file_include_theme() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = tempdir.path();
+        fs::create_dir_all(dir.join("team")).unwrap();
+        fs::write(dir.join("team/shared.toml"), "theme = ['default', 'team.theme']\n").unwrap();
+        fs::write(dir.join("team/team.theme"), "[[date]]\nproperties = ['Sunday']\nstylenames = ['FGRed']\n").unwrap();
+        fs::write(dir.join("config.toml"), "include = ['team/shared.toml']\n").unwrap();
+        let config = Config::read_file(&dir.join("config.toml")).unwrap();
+        let team_theme = dir.canonicalize().unwrap().join("team").join("team.theme").display().to_string();
+        assert_eq![config.theme, vec![String::from("default"), team_theme]];
+        let mut warnings = vec![];
+        let theme = Theme::read(&config.theme, &mut warnings).unwrap();
+        assert![warnings.is_empty()];
+        assert![theme.date.iter().any(|datestyle| datestyle.properties == vec![DateProperty::Sunday])];
+    }
+
+    #[test]
+    fn test_config_read_file_include_cycle() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = tempdir.path();
+        fs::write(dir.join("a.toml"), "include = ['b.toml']\n").unwrap();
+        fs::write(dir.join("b.toml"), "include = ['a.toml']\n").unwrap();
+        let e = Config::read_file(&dir.join("a.toml")).unwrap_err();
+        assert![e.to_string().contains("included recursively")];
+    }
+
+    #[test]
+    fn test_config_holidays() {
+        let a: Config = toml::from_str("[[holidays]]\ncountry = 'DE'\nregion = 'BY'\nstylenames = ['FGRed']\n").unwrap();
+        assert_eq![a.holidays.len(), 1];
+        assert_eq![a.holidays[0].country, "DE"];
+        assert_eq![a.holidays[0].region, Some(String::from("BY"))];
+        assert![a.astronomy.is_none()];
+    }
+
+    #[test]
+    fn test_config_astronomy() {
+        let a: Config = toml::from_str("[astronomy]
+stylenames = ['FGYellow']
+").unwrap();
+        assert_eq![a.astronomy.unwrap().stylenames.len(), 1];
+        let a: Config = toml::from_str("[astronomy]
+").unwrap();
+        assert![a.astronomy.is_some()];
+    }
+
+    #[test]
+    fn test_config_location() {
+        let a: Config = toml::from_str("[location]\nlat = 48.2\nlon = 16.37\n").unwrap();
+        assert_eq![a.location, Some(Location { lat: 48.2, lon: 16.37 })];
+        assert![a.location.unwrap().validate().is_ok()];
+        assert![Location { lat: 91.0, lon: 0.0 }.validate().is_err()];
+        assert![Location { lat: 0.0, lon: -181.0 }.validate().is_err()];
+        assert![toml::from_str::<Config>("[location]\nlat = 48.2\n").is_err()];
     }
 
     #[test]
@@ -98,4 +461,12 @@ mod tests {
         let a = IcalStyle::default();
         assert_eq![a.file, String::new()];
     }
+
+    #[test]
+    fn test_icalstyle_name() {
+        let a: IcalStyle = toml::from_str("file = \"/home/user/birthdays.ics\"").unwrap();
+        assert_eq![a.name(), "birthdays"];
+        let a: IcalStyle = toml::from_str("file = \"/home/user/birthdays.ics\"\nname = \"family\"").unwrap();
+        assert_eq![a.name(), "family"];
+    }
 }
@@ -80,7 +80,7 @@ pub enum StyleName {
     BGFixed(u8),
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub enum DateProperty {
     FirstDayOfMonth,
     BeforeFirstDayOfMonth,
@@ -105,6 +105,30 @@ pub enum DateProperty {
     BeforeToday,
     Today,
     AfterToday,
+    DayOfMonth(u32),
+    NthWeekday {
+        n: i8,
+        weekday: chrono::Weekday,
+    },
+    DateRange {
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    },
+    Month(u32),
+    WeekParity(Parity),
+    Holiday,
+    NewMoon,
+    FirstQuarter,
+    FullMoon,
+    LastQuarter,
+    Equinox,
+    Solstice,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub enum Parity {
+    Odd,
+    Even,
 }
 
 #[cfg(test)]
@@ -132,4 +156,37 @@ mod tests {
         let a = Style::default();
         assert_eq![a.weight, 0];
     }
+    #[test]
+    fn test_parametrised_dateproperties() {
+        let a: DateStyle = toml::from_str(
+            r#"
+            properties = [
+                { DayOfMonth = 15 },
+                { NthWeekday = { n = -1, weekday = "Fri" } },
+                { DateRange = { from = "2026-12-24", to = "2027-01-06" } },
+                { Month = 12 },
+                { WeekParity = "Even" },
+                "Odd",
+            ]
+            "#,
+        )
+        .unwrap();
+        assert_eq![
+            a.properties,
+            vec![
+                DateProperty::DayOfMonth(15),
+                DateProperty::NthWeekday {
+                    n: -1,
+                    weekday: chrono::Weekday::Fri
+                },
+                DateProperty::DateRange {
+                    from: chrono::NaiveDate::from_ymd_opt(2026, 12, 24).unwrap(),
+                    to: chrono::NaiveDate::from_ymd_opt(2027, 1, 6).unwrap()
+                },
+                DateProperty::Month(12),
+                DateProperty::WeekParity(Parity::Even),
+                DateProperty::Odd,
+            ]
+        ];
+    }
 }
@@ -5,44 +5,182 @@ mod components;
 
 pub use crate::config::theme::components::DateProperty::*;
 pub use crate::config::theme::components::StyleName::*;
-pub use crate::config::theme::components::{DateProperty, DateStyle, Style, StyleName, StyleType};
+pub use crate::config::theme::components::{DateProperty, DateStyle, Parity, Style, StyleName, StyleType};
+use crate::config::{describe_toml_error, string_or_list};
+use crate::error::Error;
 use clap::crate_name;
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::{Path, PathBuf};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(default)]
 pub struct Theme {
     pub date: Vec<DateStyle>,
+    pub weeknumber: Vec<Style>,
+    #[serde(deserialize_with = "string_or_list")]
+    pub extends: Vec<String>,
 }
 
+// The themes that are embedded in carl
+const BUILTIN_THEMES: &[(&str, &str)] = &[
+    ("base", include_str!("../../../themes/base.theme")),
+    ("default", include_str!("../../../themes/default.theme")),
+    ("mono", include_str!("../../../themes/mono.theme")),
+    ("seasons", include_str!("../../../themes/seasons.theme")),
+];
+
 impl Theme {
+    /// Read the themes `themes`, see [`Theme::find`], and stack them: the
+    /// date styles of later themes are added after those of earlier
+    /// themes. If none of the themes exists, the default theme is used.
+    /// Missing themes are reported as warnings.
     #[cfg(not(tarpaulin_include))]
-    pub fn read(theme: &Option<String>) -> Theme {
-        if let Some(themename) = theme {
-            if let Some(project_dirs) = directories::ProjectDirs::from("org", "bisco", crate_name!()) {
-                let theme_file = project_dirs.config_dir().join(format!("{}.theme", themename));
-                if theme_file.exists() {
-                    let theme_content = fs::read_to_string(theme_file).unwrap_or_default();
-                    match toml::from_str(&theme_content) {
-                        Ok(theme) => return theme,
-                        Err(e) => eprintln!("Could not parse theme file: {}", e),
+    pub fn read(themes: &[String], warnings: &mut Vec<Error>) -> Result<Theme, Error> {
+        let mut theme = Theme::empty();
+        let mut found = false;
+        for themename in themes {
+            if let Some(layer) = Theme::load(themename, false, &mut vec![], warnings)? {
+                theme.stack(layer);
+                found = true;
+            }
+        }
+        Ok(if found { theme } else { Theme::default() })
+    }
+
+    // Load the theme `themename` on top of the themes it extends. If a
+    // theme extends a theme with its own name, the builtin theme with
+    // that name is used. `stack` contains the themes that are currently
+    // loaded, to detect themes that extend themselves.
+    fn load(themename: &str, builtin_only: bool, stack: &mut Vec<String>, warnings: &mut Vec<Error>) -> Result<Option<Theme>, Error> {
+        let theme_error = |message: String| Error::Theme {
+            name: themename.to_string(),
+            message,
+        };
+        let found = if builtin_only {
+            Theme::find_builtin(themename)
+        } else {
+            Theme::find(themename)?
+        };
+        let Some((origin, content)) = found else {
+            warnings.push(theme_error(String::from("no such theme")));
+            return Ok(None);
+        };
+        let key = format!("{}:{}", origin, themename);
+        if stack.contains(&key) {
+            return Err(theme_error(format!("{}: the theme extends itself", origin)));
+        }
+        let mut layer: Theme = toml::from_str(&content).map_err(|e| theme_error(format!("{}: {}", origin, describe_toml_error(&content, &e))))?;
+
+        stack.push(key);
+        let mut theme = Theme::empty();
+        for parent in std::mem::take(&mut layer.extends) {
+            let parent = Theme::parent(&origin, parent);
+            if let Some(parent) = Theme::load(&parent, parent == themename, stack, warnings)? {
+                theme.stack(parent);
+            }
+        }
+        stack.pop();
+        theme.stack(layer);
+        Ok(Some(theme))
+    }
+
+    /// The name of the theme `parent` extended by the theme found at
+    /// `origin`. Paths are relative to the directory of the themefile.
+    pub fn parent(origin: &str, parent: String) -> String {
+        match Path::new(origin).parent() {
+            Some(dir) if is_path(&parent) && origin != "builtin" => dir.join(&parent).display().to_string(),
+            _ => parent,
+        }
+    }
+
+    // A theme without any styles
+    fn empty() -> Theme {
+        Theme {
+            date: vec![],
+            weeknumber: vec![],
+            extends: vec![],
+        }
+    }
+
+    fn stack(&mut self, mut other: Theme) {
+        self.date.append(&mut other.date);
+        self.weeknumber.append(&mut other.weeknumber);
+    }
+
+    /// Find the theme `themename` and return where it was found and its
+    /// content. `themename` is either the path of a themefile, if it
+    /// contains a `/` or ends with `.theme`, the name of a themefile in
+    /// the configuration directory or the name of a builtin theme.
+    pub fn find(themename: &str) -> Result<Option<(String, String)>, Error> {
+        let theme_file = if is_path(themename) {
+            Some(PathBuf::from(themename))
+        } else {
+            Theme::path(themename)
+        };
+        if let Some(theme_file) = theme_file.filter(|path| path.exists()) {
+            let content = fs::read_to_string(&theme_file).map_err(|e| Error::Theme {
+                name: themename.to_string(),
+                message: format!("{}: {}", theme_file.display(), e),
+            })?;
+            return Ok(Some((theme_file.display().to_string(), content)));
+        }
+        Ok(Theme::find_builtin(themename))
+    }
+
+    fn find_builtin(themename: &str) -> Option<(String, String)> {
+        BUILTIN_THEMES
+            .iter()
+            .find(|(name, _)| *name == themename)
+            .map(|(_, content)| (String::from("builtin"), content.to_string()))
+    }
+
+    /// The names of all the available themes and where they are located,
+    /// ordered by name. Themes in the configuration directory hide the
+    /// builtin themes with the same name.
+    pub fn list() -> Vec<(String, String)> {
+        let mut themes: Vec<(String, String)> = vec![];
+        if let Some(entries) = config_dir().and_then(|dir| dir.read_dir().ok()) {
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if path.extension().is_some_and(|extension| extension == "theme") {
+                    if let Some(name) = path.file_stem() {
+                        themes.push((name.to_string_lossy().to_string(), path.display().to_string()));
                     }
-                } else {
-                    eprintln!("Could not load theme file {}.theme, using builtin theme.", themename);
                 }
-            } else {
-                eprintln!("Cannot determine config directory, using builtin theme.");
             }
         }
-        Theme::default()
+        for (name, _) in BUILTIN_THEMES {
+            if !themes.iter().any(|(themename, _)| themename == name) {
+                themes.push((name.to_string(), String::from("builtin")));
+            }
+        }
+        themes.sort();
+        themes
+    }
+
+    /// The location of the themefile of the theme `themename` in the
+    /// configuration directory, if a configuration directory could be
+    /// determined.
+    pub fn path(themename: &str) -> Option<PathBuf> {
+        Some(config_dir()?.join(format!("{}.theme", themename)))
     }
 }
 
+pub(super) fn is_path(themename: &str) -> bool {
+    themename.contains('/') || themename.ends_with(".theme")
+}
+
+fn config_dir() -> Option<PathBuf> {
+    let project_dirs = directories::ProjectDirs::from("org", "bisco", crate_name!())?;
+    Some(project_dirs.config_dir().to_path_buf())
+}
+
 impl Default for Theme {
     fn default() -> Self {
         Theme {
             date: default_datestyle_vector(),
+            weeknumber: vec![],
+            extends: vec![],
         }
     }
 }
@@ -95,15 +233,77 @@ mod tests {
 
     #[test]
     fn test_theme_read_none() {
-        let a = Theme::read(&None);
+        let mut warnings = vec![];
+        let a = Theme::read(&[], &mut warnings).unwrap();
         assert![!a.date.is_empty()];
+        assert![warnings.is_empty()];
     }
 
     #[test]
     fn test_theme_read_filename() {
         env::set_var("XDG_CONFIG_DIRS", "/nonexistent");
         env::set_var("XDG_CONFIG_HOME", "/nonexistent");
-        let a = Theme::read(&Some(String::from("nonexistent")));
+        let mut warnings = vec![];
+        let a = Theme::read(&[String::from("nonexistent")], &mut warnings).unwrap();
         assert![!a.date.is_empty()];
+        assert_eq![warnings.len(), 1];
+        assert![matches!(warnings[0], Error::Theme { .. })];
+    }
+
+    #[test]
+    fn test_builtin_themes() {
+        for (name, content) in BUILTIN_THEMES {
+            let theme: Result<Theme, _> = toml::from_str(content);
+            assert![theme.is_ok(), "builtin theme {} is invalid", name];
+        }
+    }
+
+    #[test]
+    fn test_theme_find_builtin() {
+        let (origin, _) = Theme::find("mono").unwrap().unwrap();
+        assert_eq![origin, "builtin"];
+        assert![Theme::find("nonexistent").unwrap().is_none()];
+    }
+
+    #[test]
+    fn test_theme_find_path() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/themes/seasons.theme");
+        let (origin, content) = Theme::find(path).unwrap().unwrap();
+        assert_eq![origin, path];
+        assert![content.contains("Month")];
+        assert![Theme::find("./nonexistent.theme").unwrap().is_none()];
+    }
+
+    #[test]
+    fn test_theme_read_extends() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = tempdir.path();
+        fs::write(dir.join("child.theme"), "extends = ['base', 'parent.theme']\n[[date]]\nproperties = ['Today']\nstylenames = ['Bold']\n").unwrap();
+        fs::write(dir.join("parent.theme"), "[[date]]\nproperties = ['Sunday']\nstylenames = ['FGRed']\n").unwrap();
+        let mut warnings = vec![];
+        let a = Theme::read(&[dir.join("child.theme").display().to_string()], &mut warnings).unwrap();
+        assert![warnings.is_empty()];
+        let properties: Vec<DateProperty> = a.date.into_iter().flat_map(|datestyle| datestyle.properties).collect();
+        assert_eq![properties, vec![BeforeFirstDayOfMonth, AfterLastDayOfMonth, Sunday, Today]];
+    }
+
+    #[test]
+    fn test_theme_read_extends_itself() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = tempdir.path();
+        fs::write(dir.join("a.theme"), "extends = 'b.theme'\n").unwrap();
+        fs::write(dir.join("b.theme"), "extends = 'a.theme'\n").unwrap();
+        let e = Theme::read(&[dir.join("a.theme").display().to_string()], &mut vec![]).unwrap_err();
+        assert![e.to_string().contains("extends itself")];
+    }
+
+    #[test]
+    fn test_theme_read_stacked() {
+        let mut warnings = vec![];
+        let a = Theme::read(&[String::from("base"), String::from("mono"), String::from("nonexistent")], &mut warnings).unwrap();
+        assert_eq![warnings.len(), 1];
+        let mono: Theme = toml::from_str(&Theme::find_builtin("mono").unwrap().1).unwrap();
+        // mono extends base, too
+        assert_eq![a.date.len(), 2 + 2 + mono.date.len()];
     }
 }
@@ -2,10 +2,13 @@
 //
 // SPDX-License-Identifier: MIT
 
+use crate::calendar::{calendar_system, Calendar};
 use crate::cli::{Action, Cli};
-use crate::config::StyleType;
+use crate::config::{Style, StyleType, StyleTypeSetting};
 use crate::config::{Config, Theme};
-use crate::utils::DateExtensions;
+use crate::error::Error;
+use crate::events::{EventInstances, Events, FromAstronomy, FromHolidays, ReadFromIcsFile};
+use crate::utils::{detect_styletype, resolve_locale, starts_with_sunday, DatePrecision, DateSpan, ParsedDate};
 use chrono::prelude::*;
 use clap::Parser;
 use serde::Deserialize;
@@ -13,6 +16,9 @@ use serde::Deserialize;
 // A struct storing the combined settings of config file, theme, options, ...
 #[derive(Deserialize, Debug)]
 pub struct Context {
+    pub today: chrono::NaiveDate,
+    // The current time, the start of `today` if it was given
+    pub now: chrono::NaiveDateTime,
     pub specified_date: Option<chrono::NaiveDate>,
     pub usersetdate: chrono::NaiveDate,
     pub opts: Cli,
@@ -22,64 +28,176 @@ pub struct Context {
     pub begin: chrono::NaiveDate,
     pub end: chrono::NaiveDate,
     pub columns: usize,
+    // The locale for month and weekday names, `None` is the C locale
+    #[serde(skip)]
+    pub locale: Option<chrono::Locale>,
+    // The calendar used for the months of the calendar grid
+    #[serde(skip)]
+    pub calendar: Calendar,
+    // The name of the calendar system of the secondary dates
+    #[serde(skip)]
+    pub secondary_calendar: Option<String>,
+    // The events read from the configured ics files, with their style
+    #[serde(skip)]
+    pub events: Vec<(Events, Style)>,
+    // The number of entries in `events` read from ics files, the other
+    // entries are generated for the displayed timespan
+    #[serde(skip)]
+    ics_events: usize,
+    // Problems that did not stop carl, but might be unexpected
+    #[serde(skip)]
+    pub warnings: Vec<Error>,
 }
 
 impl Context {
-    pub fn new() -> Result<Context, String> {
-        let mut opts: Cli = Cli::parse();
-        let config: Config = Config::read();
-        let theme: Theme = if opts.theme.is_some() {
-            Theme::read(&opts.theme)
+    pub fn new() -> Result<Context, Error> {
+        Context::from_opts(Cli::parse())
+    }
+
+    /// Create the context from already parsed commandline options,
+    /// reading the configuration and the theme.
+    pub fn from_opts(opts: Cli) -> Result<Context, Error> {
+        let config: Config = Config::read(&opts.config)?;
+        let mut warnings: Vec<Error> = vec![];
+        let theme: Theme = if opts.theme.is_empty() {
+            Theme::read(&config.theme, &mut warnings)?
         } else {
-            Theme::read(&config.theme)
+            Theme::read(&opts.theme, &mut warnings)?
         };
+        let mut ctx = Context::from_parts(opts, config, theme)?;
+        warnings.append(&mut ctx.warnings);
+        ctx.warnings = warnings;
+        Ok(ctx)
+    }
 
-        let styletype: StyleType = if opts.themestyletype == Some(String::from("dark")) {
-            StyleType::Dark
-        } else {
-            StyleType::Light
+    /// Create the context from already parsed commandline options and
+    /// already loaded configuration and theme.
+    pub fn from_parts(mut opts: Cli, mut config: Config, theme: Theme) -> Result<Context, Error> {
+        let mut warnings: Vec<Error> = vec![];
+
+        let styletype_setting = match opts.themestyletype.as_deref() {
+            Some("dark") => Some(StyleTypeSetting::Dark),
+            Some("light") => Some(StyleTypeSetting::Light),
+            Some("auto") => Some(StyleTypeSetting::Auto),
+            _ => config.styletype.clone(),
+        };
+        let styletype: StyleType = match styletype_setting {
+            Some(StyleTypeSetting::Dark) => StyleType::Dark,
+            Some(StyleTypeSetting::Auto) => detect_styletype().unwrap_or(StyleType::Light),
+            _ => StyleType::Light,
+        };
+
+        let locale = match resolve_locale(config.locale.as_deref()) {
+            Ok(locale) => locale,
+            Err(message) => {
+                warnings.push(Error::Setting {
+                    name: String::from("locale"),
+                    message,
+                });
+                None
+            }
+        };
+        if !opts.sunday && !opts.monday {
+            opts.sunday = locale.is_some_and(starts_with_sunday);
+        }
+
+        let calendar = match opts.reform.as_deref().or(config.reform.as_deref()).map(|reform| (reform, Calendar::from_name(reform))) {
+            Some((_, Some(calendar))) => calendar,
+            Some((reform, None)) => {
+                warnings.push(Error::Setting {
+                    name: String::from("reform"),
+                    message: format!("unknown reform {}: use 1582, 1752, gregorian or julian", reform),
+                });
+                Calendar::default()
+            }
+            None => Calendar::default(),
+        };
+        let secondary_calendar = match opts.secondary_calendar.clone().or(config.secondary_calendar.clone()) {
+            Some(name) if calendar_system(&name).is_none() => {
+                warnings.push(Error::Setting {
+                    name: String::from("secondary_calendar"),
+                    message: format!("unknown calendar {}: use gregorian, julian, hebrew, islamic or persian", name),
+                });
+                None
+            }
+            name => name,
         };
 
-        let usersetdate: chrono::NaiveDate = opts.validate_date()?;
-        let specified_date = if opts.date.len() == 3 { Some(usersetdate) } else { None };
+        let now: chrono::NaiveDateTime = match opts.today {
+            Some(today) => today.and_time(NaiveTime::MIN),
+            None => Local::now().naive_local(),
+        };
+        let today: chrono::NaiveDate = now.date();
+        let parsed_date: ParsedDate = opts.validate_date(today, &calendar)?;
+        let usersetdate: chrono::NaiveDate = parsed_date.date;
+        let specified_date = if parsed_date.precision == DatePrecision::Day { Some(usersetdate) } else { None };
 
         if opts.action == Action::default() {
             opts.action.calendar = true;
         }
 
-        let mut columns = 1;
-        if opts.three || opts.year || opts.months.is_some() {
-            columns = 3;
-        }
-
-        let mut begin = usersetdate.first_day_of_month();
-        let mut end = usersetdate.last_day_of_month();
+        let span: DateSpan = date_span(&opts, usersetdate, &calendar)?;
+        let columns = columns(&span, &calendar);
+        let begin = span.begin;
+        let end = span.end;
 
-        if opts.three {
-            begin = usersetdate
-                .first_day_of_month()
-                .pred_opt()
-                .unwrap()
-                .first_day_of_month();
-            end = usersetdate.first_day_of_next_month().last_day_of_month();
+        if let Some(template_dir) = &config.template_dir {
+            if config.template().is_none() {
+                warnings.push(Error::Setting {
+                    name: String::from("template_dir"),
+                    message: format!("{} is not a directory", template_dir),
+                });
+            }
         }
 
-        if let Some(num) = opts.months {
-            begin = usersetdate.first_day_of_month();
-            let mut tmpdate = begin;
-            for _ in 1..=(num - 1) {
-                tmpdate = tmpdate.first_day_of_next_month();
+        if let Some(Err(message)) = config.location.map(|location| location.validate()) {
+            let e = Error::Setting {
+                name: String::from("location"),
+                message,
+            };
+            if opts.action.daylight {
+                return Err(e);
             }
-            end = tmpdate.last_day_of_month();
+            warnings.push(e);
+            config.location = None;
         }
-
-        if opts.year {
-            begin = usersetdate.first_day_of_year();
-            end = usersetdate.last_day_of_year();
+        if opts.action.daylight && config.location.is_none() {
+            return Err(Error::Setting {
+                name: String::from("location"),
+                message: String::from("--daylight needs the `lat` and `lon` of the location"),
+            });
         }
 
+        for name in &opts.ical {
+            if !config.ical.iter().any(|icalstyle| &icalstyle.name() == name) {
+                warnings.push(Error::Setting {
+                    name: String::from("ical"),
+                    message: format!("--ical {}: there is no ical entry with this name", name),
+                });
+            }
+        }
+        let mut events = vec![];
+        for icalstyle in &config.ical {
+            if !opts.ical.is_empty() && !opts.ical.contains(&icalstyle.name()) {
+                continue;
+            }
+            match Events::read_from_ics_file(&icalstyle.file, &mut warnings) {
+                Ok(mut x) => {
+                    if let Some(filter) = &icalstyle.filter {
+                        x.retain(|event| event.matches_text(filter));
+                    }
+                    filter_events(&mut x, &opts);
+                    events.push((x, icalstyle.style.clone()));
+                }
+                Err(e) => warnings.push(e),
+            }
+        }
+        let ics_events = events.len();
+        events.append(&mut generated_events(&config, &opts, begin, end, &mut warnings));
 
         Ok(Context {
+            today,
+            now,
             specified_date,
             usersetdate,
             opts,
@@ -89,22 +207,162 @@ impl Context {
             begin,
             end,
             columns,
+            locale,
+            calendar,
+            secondary_calendar,
+            events,
+            ics_events,
+            warnings,
         })
     }
+
+    /// Show `date` instead of the date passed on the commandline, e.g.
+    /// when it is selected in the interactive mode. The displayed
+    /// timespan moves along with the date.
+    pub fn set_date(&mut self, date: chrono::NaiveDate) -> Result<(), Error> {
+        let span = date_span(&self.opts, date, &self.calendar)?;
+        self.usersetdate = date;
+        self.specified_date = Some(date);
+        self.columns = columns(&span, &self.calendar);
+        if (span.begin, span.end) != (self.begin, self.end) {
+            self.begin = span.begin;
+            self.end = span.end;
+            // Problems were already reported when creating the context
+            self.events.truncate(self.ics_events);
+            self.events.append(&mut generated_events(&self.config, &self.opts, self.begin, self.end, &mut vec![]));
+        }
+        Ok(())
+    }
+}
+
+/// The timespan to show for `usersetdate`, depending on the options.
+fn date_span(opts: &Cli, usersetdate: chrono::NaiveDate, calendar: &Calendar) -> Result<DateSpan, Error> {
+    let span = if opts.action.day {
+        DateSpan::day(usersetdate)
+    } else if opts.action.week {
+        DateSpan::week(usersetdate, opts.sunday)
+    } else if opts.from.is_some() || opts.to.is_some() {
+        let begin = opts.from.unwrap_or(calendar.first_day_of_month(usersetdate));
+        let end = opts.to.unwrap_or(calendar.last_day_of_month(begin));
+        if end < begin {
+            return Err(Error::Usage(String::from("--to date has to be after --from date")));
+        }
+        DateSpan::new(begin, end)
+    } else if opts.year {
+        DateSpan::year(usersetdate, calendar)
+    } else {
+        let mut before = opts.before.unwrap_or_default();
+        let mut after = opts.after.unwrap_or_default();
+        if opts.three {
+            before += 1;
+            after += 1;
+        }
+        if let Some(num) = opts.months {
+            after += u32::from(num) - 1;
+        }
+        DateSpan::months(usersetdate, before, after, calendar)
+    };
+    Ok(span)
+}
+
+fn columns(span: &DateSpan, calendar: &Calendar) -> usize {
+    if span.number_of_months(calendar) > 1 {
+        3
+    } else {
+        1
+    }
+}
+
+/// Keep only the events that match the `--grep` and the `--category`
+/// options. Of several values of one option, one has to match.
+fn filter_events(events: &mut Events, opts: &Cli) {
+    events.retain(|event| {
+        (opts.grep.is_empty() || opts.grep.iter().any(|pattern| event.matches_text(pattern)))
+            && (opts.category.is_empty() || opts.category.iter().any(|category| event.has_category(category)))
+    });
+}
+
+/// The events that are computed for the displayed timespan instead of
+/// being read from files: holidays and astronomical events. There are
+/// none if only the events of some ical entries are selected.
+fn generated_events(config: &Config, opts: &Cli, begin: chrono::NaiveDate, end: chrono::NaiveDate, warnings: &mut Vec<Error>) -> Vec<(Events, Style)> {
+    let mut events = vec![];
+    if !opts.ical.is_empty() {
+        return events;
+    }
+    if let Some(style) = &config.astronomy {
+        events.push((Events::from_astronomy(begin, end), style.clone()));
+    }
+    for holidaystyle in &config.holidays {
+        match Events::from_holidays(&holidaystyle.country, holidaystyle.region.as_deref(), begin.year()..=end.year()) {
+            Ok(x) => events.push((x, holidaystyle.style.clone())),
+            Err(e) => warnings.push(e),
+        }
+    }
+    for (events, _) in &mut events {
+        filter_events(events, opts);
+    }
+    events
+}
+
+impl Context {
+    /// Collect the instances of the events from all the configured
+    /// ics files that occur between `begin` and `end`, ordered by date.
+    pub fn event_instances(&self) -> EventInstances {
+        self.event_instances_between(self.begin, self.end)
+    }
+
+    /// Collect the instances of the events that occur between `begin`
+    /// and `end`, which can be outside of the displayed timespan.
+    pub fn event_instances_between(&self, begin: chrono::NaiveDate, end: chrono::NaiveDate) -> EventInstances {
+        let generated;
+        let events: Vec<&(Events, Style)> = if self.begin <= begin && end <= self.end {
+            self.events.iter().collect()
+        } else {
+            generated = generated_events(&self.config, &self.opts, begin, end, &mut vec![]);
+            self.events[..self.ics_events].iter().chain(&generated).collect()
+        };
+        let mut event_instances = vec![];
+        for (events, style) in events {
+            for event in events {
+                event_instances.append(&mut event.instances(&begin, &end, style));
+            }
+        }
+        event_instances.sort_by_key(|a| a.date);
+        event_instances
+    }
+
+    /// The instances of the events in the `days` days starting with the
+    /// day of `now` that did not start before `now`, ordered by their
+    /// start.
+    pub fn upcoming_event_instances(&self, now: chrono::NaiveDateTime, days: i64) -> EventInstances {
+        let mut event_instances = self.event_instances_between(now.date(), now.date() + chrono::Duration::days(days));
+        event_instances.retain(|instance| instance.date > now.date() || instance.start_time().is_none_or(|time| time >= now.time()));
+        event_instances.sort_by_key(|instance| (instance.date, instance.start_time()));
+        event_instances
+    }
 }
 
 impl Default for Context {
     fn default() -> Self {
         Context {
+            today: NaiveDate::default(),
+            now: NaiveDateTime::default(),
             specified_date: None,
             usersetdate: NaiveDate::default(),
-            opts: Cli::parse(),
+            opts: Cli::default(),
             config: Config::default(),
             theme: Theme::default(),
             styletype: StyleType::Light,
             begin: NaiveDate::default(),
             end: NaiveDate::default(),
             columns: 1,
+            locale: None,
+            calendar: Calendar::default(),
+            secondary_calendar: None,
+            events: vec![],
+            ics_events: 0,
+            warnings: vec![],
         }
     }
 }
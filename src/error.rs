@@ -0,0 +1,110 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+use std::fmt;
+use std::path::PathBuf;
+
+/// The errors (and warnings) carl can run into. Every kind of error has
+/// its own exit code, see [`Error::exit_code`].
+#[derive(Debug)]
+pub enum Error {
+    /// An invalid commandline argument, e.g. a date that does not exist
+    Usage(String),
+    /// The configuration file can not be read or parsed
+    Config { path: PathBuf, message: String },
+    /// A setting in the configuration has an invalid value
+    Setting { name: String, message: String },
+    /// The theme could not be found or is invalid
+    Theme { name: String, message: String },
+    /// An ics file or directory could not be read
+    Ics { path: PathBuf, message: String },
+    /// A template could not be rendered
+    Template(minijinja::Error),
+    /// The terminal could not be used for the interactive mode
+    Terminal(std::io::Error),
+}
+
+impl Error {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Usage(_) => 1,
+            Error::Config { .. } | Error::Setting { .. } => 3,
+            Error::Theme { .. } => 4,
+            Error::Ics { .. } => 5,
+            Error::Template(_) => 6,
+            Error::Terminal(_) => 7,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Usage(message) => write!(f, "{}", message),
+            Error::Config { path, message } => write!(f, "config file {}: {}", path.display(), message),
+            Error::Setting { name, message } => write!(f, "config setting {}: {}", name, message),
+            Error::Theme { name, message } => write!(f, "theme {}: {}", name, message),
+            Error::Ics { path, message } => write!(f, "ics file {}: {}", path.display(), message),
+            Error::Template(e) => write!(f, "template error: {:#}", e),
+            Error::Terminal(e) => write!(f, "terminal error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Template(e) => Some(e),
+            Error::Terminal(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<minijinja::Error> for Error {
+    fn from(e: minijinja::Error) -> Self {
+        Error::Template(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Terminal(e)
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Usage(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_differ() {
+        let errors = [
+            Error::Usage(String::new()),
+            Error::Config { path: PathBuf::new(), message: String::new() },
+            Error::Theme { name: String::new(), message: String::new() },
+            Error::Ics { path: PathBuf::new(), message: String::new() },
+            Error::Template(minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, "")),
+            Error::Terminal(std::io::Error::other("")),
+        ];
+        let mut codes: Vec<i32> = errors.iter().map(|e| e.exit_code()).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+        assert!(!codes.contains(&0));
+        // clap uses exit code 2 for invalid arguments
+        assert!(!codes.contains(&2));
+    }
+    #[test]
+    fn test_display_contains_context() {
+        let e = Error::Ics { path: PathBuf::from("/tmp/foo.ics"), message: String::from("No such file") };
+        assert_eq!(e.to_string(), "ics file /tmp/foo.ics: No such file");
+    }
+}
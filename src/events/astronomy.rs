@@ -0,0 +1,65 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+//! Moon phases, equinoxes and solstices as events.
+
+use crate::events::{Event, EventDateTime, Events};
+use crate::utils::{moon_phases, seasons};
+use chrono::prelude::*;
+
+/// The category of the events created for moon phases, equinoxes and
+/// solstices.
+pub const ASTRONOMY_CATEGORY: &str = "Astronomy";
+
+pub trait FromAstronomy {
+    fn from_astronomy(from: NaiveDate, to: NaiveDate) -> Events;
+}
+
+impl FromAstronomy for Events {
+    /// Create an event for every moon phase, equinox and solstice
+    /// between `from` and `to`.
+    fn from_astronomy(from: NaiveDate, to: NaiveDate) -> Events {
+        let mut times: Vec<(DateTime<Local>, String)> = moon_phases(from, to)
+            .into_iter()
+            .map(|(time, phase)| (time, phase.to_string()))
+            .collect();
+        for year in from.year()..=to.year() {
+            times.extend(seasons(year).into_iter().map(|(time, season)| (time, season.to_string())));
+        }
+        times.retain(|(time, _)| from <= time.date_naive() && time.date_naive() <= to);
+        times.sort();
+        times
+            .into_iter()
+            .map(|(time, summary)| {
+                let start = EventDateTime::DateTime {
+                    date_time: time.naive_local(),
+                    offset: None,
+                };
+                Event {
+                    start,
+                    end: start,
+                    rrulesets: vec![],
+                    summary,
+                    categories: vec![String::from(ASTRONOMY_CATEGORY)],
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_astronomy() {
+        let from = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2025, 12, 31).unwrap();
+        let events = Events::from_astronomy(from, to);
+        assert_eq!(events.iter().filter(|event| event.summary == "Full Moon").count(), 12);
+        assert_eq!(events.iter().filter(|event| event.summary.ends_with("Solstice")).count(), 2);
+        assert!(events.windows(2).all(|pair| pair[0].start.date() <= pair[1].start.date()));
+        assert!(events.iter().all(|event| event.categories == vec![ASTRONOMY_CATEGORY]));
+    }
+}
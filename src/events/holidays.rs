@@ -0,0 +1,269 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+//! Public holidays, computed from a set of rules per country, without
+//! the need for an ics file.
+
+use crate::error::Error;
+use crate::events::{Event, EventDateTime, Events};
+use crate::utils::DateExtensions;
+use chrono::prelude::*;
+use chrono::Duration;
+use std::ops::RangeInclusive;
+
+/// The category of the events created for public holidays.
+pub const HOLIDAY_CATEGORY: &str = "Holiday";
+
+enum Rule {
+    /// A fixed date, month and day
+    Fixed(u32, u32),
+    /// A number of days before or after Easter Sunday
+    Easter(i64),
+    /// The nth weekday of a month, negative n count from the end of
+    /// the month
+    NthWeekday(u32, i8, Weekday),
+    /// The last wednesday before November 23rd
+    RepentanceAndPrayer,
+}
+
+struct Holiday {
+    name: &'static str,
+    rule: Rule,
+    /// The regions the holiday applies to, all regions if empty
+    regions: &'static [&'static str],
+    /// The first year of the holiday
+    since: i32,
+}
+
+const fn holiday(name: &'static str, rule: Rule) -> Holiday {
+    Holiday {
+        name,
+        rule,
+        regions: &[],
+        since: i32::MIN,
+    }
+}
+
+const fn regional(name: &'static str, rule: Rule, regions: &'static [&'static str], since: i32) -> Holiday {
+    Holiday { name, rule, regions, since }
+}
+
+const AT: &[Holiday] = &[
+    holiday("Neujahr", Rule::Fixed(1, 1)),
+    holiday("Heilige Drei Könige", Rule::Fixed(1, 6)),
+    holiday("Ostermontag", Rule::Easter(1)),
+    holiday("Staatsfeiertag", Rule::Fixed(5, 1)),
+    holiday("Christi Himmelfahrt", Rule::Easter(39)),
+    holiday("Pfingstmontag", Rule::Easter(50)),
+    holiday("Fronleichnam", Rule::Easter(60)),
+    holiday("Mariä Himmelfahrt", Rule::Fixed(8, 15)),
+    holiday("Nationalfeiertag", Rule::Fixed(10, 26)),
+    holiday("Allerheiligen", Rule::Fixed(11, 1)),
+    holiday("Mariä Empfängnis", Rule::Fixed(12, 8)),
+    holiday("Christtag", Rule::Fixed(12, 25)),
+    holiday("Stefanitag", Rule::Fixed(12, 26)),
+];
+
+const DE: &[Holiday] = &[
+    holiday("Neujahr", Rule::Fixed(1, 1)),
+    regional("Heilige Drei Könige", Rule::Fixed(1, 6), &["BW", "BY", "ST"], i32::MIN),
+    regional("Internationaler Frauentag", Rule::Fixed(3, 8), &["BE"], 2019),
+    regional("Internationaler Frauentag", Rule::Fixed(3, 8), &["MV"], 2023),
+    holiday("Karfreitag", Rule::Easter(-2)),
+    regional("Ostersonntag", Rule::Easter(0), &["BB"], i32::MIN),
+    holiday("Ostermontag", Rule::Easter(1)),
+    holiday("Tag der Arbeit", Rule::Fixed(5, 1)),
+    holiday("Christi Himmelfahrt", Rule::Easter(39)),
+    regional("Pfingstsonntag", Rule::Easter(49), &["BB"], i32::MIN),
+    holiday("Pfingstmontag", Rule::Easter(50)),
+    regional("Fronleichnam", Rule::Easter(60), &["BW", "BY", "HE", "NW", "RP", "SL"], i32::MIN),
+    regional("Mariä Himmelfahrt", Rule::Fixed(8, 15), &["SL"], i32::MIN),
+    regional("Weltkindertag", Rule::Fixed(9, 20), &["TH"], 2019),
+    holiday("Tag der Deutschen Einheit", Rule::Fixed(10, 3)),
+    regional("Reformationstag", Rule::Fixed(10, 31), &["BB", "MV", "SN", "ST", "TH"], i32::MIN),
+    regional("Reformationstag", Rule::Fixed(10, 31), &["HB", "HH", "NI", "SH"], 2018),
+    regional("Allerheiligen", Rule::Fixed(11, 1), &["BW", "BY", "NW", "RP", "SL"], i32::MIN),
+    regional("Buß- und Bettag", Rule::RepentanceAndPrayer, &["SN"], i32::MIN),
+    holiday("1. Weihnachtstag", Rule::Fixed(12, 25)),
+    holiday("2. Weihnachtstag", Rule::Fixed(12, 26)),
+];
+const DE_REGIONS: &[&str] = &[
+    "BB", "BE", "BW", "BY", "HB", "HE", "HH", "MV", "NI", "NW", "RP", "SH", "SL", "SN", "ST", "TH",
+];
+
+const US: &[Holiday] = &[
+    holiday("New Year's Day", Rule::Fixed(1, 1)),
+    holiday("Martin Luther King Jr. Day", Rule::NthWeekday(1, 3, Weekday::Mon)),
+    holiday("Washington's Birthday", Rule::NthWeekday(2, 3, Weekday::Mon)),
+    holiday("Memorial Day", Rule::NthWeekday(5, -1, Weekday::Mon)),
+    regional("Juneteenth", Rule::Fixed(6, 19), &[], 2021),
+    holiday("Independence Day", Rule::Fixed(7, 4)),
+    holiday("Labor Day", Rule::NthWeekday(9, 1, Weekday::Mon)),
+    holiday("Columbus Day", Rule::NthWeekday(10, 2, Weekday::Mon)),
+    holiday("Veterans Day", Rule::Fixed(11, 11)),
+    holiday("Thanksgiving Day", Rule::NthWeekday(11, 4, Weekday::Thu)),
+    holiday("Christmas Day", Rule::Fixed(12, 25)),
+];
+
+/// The countries (and their regions) public holidays are known for.
+pub const COUNTRIES: &[(&str, &[&str])] = &[("AT", &[]), ("DE", DE_REGIONS), ("US", &[])];
+
+fn rules(country: &str) -> Option<&'static [Holiday]> {
+    match country {
+        "AT" => Some(AT),
+        "DE" => Some(DE),
+        "US" => Some(US),
+        _ => None,
+    }
+}
+
+/// Easter Sunday of the given year in the gregorian calendar, using the
+/// anonymous gregorian algorithm.
+pub fn easter_sunday(year: i32) -> NaiveDate {
+    let a = year.rem_euclid(19);
+    let b = year.div_euclid(100);
+    let c = year.rem_euclid(100);
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).unwrap()
+}
+
+impl Rule {
+    fn date(&self, year: i32) -> Option<NaiveDate> {
+        match self {
+            Rule::Fixed(month, day) => NaiveDate::from_ymd_opt(year, *month, *day),
+            Rule::Easter(offset) => Some(easter_sunday(year) + Duration::days(*offset)),
+            Rule::NthWeekday(month, n, weekday) => {
+                if *n < 0 {
+                    let last = NaiveDate::from_ymd_opt(year, *month, 1)?.last_day_of_month();
+                    let offset = (last.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+                    Some(last - Duration::days(i64::from(offset) + 7 * i64::from(-*n - 1)))
+                } else {
+                    NaiveDate::from_weekday_of_month_opt(year, *month, *weekday, *n as u8)
+                }
+            }
+            Rule::RepentanceAndPrayer => {
+                let november22 = NaiveDate::from_ymd_opt(year, 11, 22)?;
+                let offset = (november22.weekday().num_days_from_monday() + 7 - Weekday::Wed.num_days_from_monday()) % 7;
+                Some(november22 - Duration::days(offset.into()))
+            }
+        }
+    }
+}
+
+/// The public holidays of `country` in `year`, ordered by date. If a
+/// `region` is given, the holidays of that region are included.
+pub fn holidays(country: &str, region: Option<&str>, year: i32) -> Result<Vec<(NaiveDate, &'static str)>, String> {
+    let country = country.to_uppercase();
+    let holidays = rules(&country).ok_or(format!("no holidays known for country {}", country))?;
+    let region = match region {
+        Some(region) => {
+            let region = region.to_uppercase();
+            let region = region.strip_prefix(&format!("{}-", country)).unwrap_or(&region).to_string();
+            let regions = COUNTRIES.iter().find(|(name, _)| *name == country).map(|(_, regions)| *regions).unwrap_or_default();
+            if !regions.contains(&region.as_str()) {
+                return Err(format!("unknown region {} of country {}", region, country));
+            }
+            Some(region)
+        }
+        None => None,
+    };
+    let mut dates: Vec<(NaiveDate, &'static str)> = holidays
+        .iter()
+        .filter(|holiday| year >= holiday.since)
+        .filter(|holiday| holiday.regions.is_empty() || region.as_ref().is_some_and(|region| holiday.regions.contains(&region.as_str())))
+        .filter_map(|holiday| Some((holiday.rule.date(year)?, holiday.name)))
+        .collect();
+    dates.sort();
+    Ok(dates)
+}
+
+pub trait FromHolidays {
+    fn from_holidays(country: &str, region: Option<&str>, years: RangeInclusive<i32>) -> Result<Events, Error>;
+}
+
+impl FromHolidays for Events {
+    /// Create an all-day event for every public holiday of `country` in
+    /// the given years.
+    fn from_holidays(country: &str, region: Option<&str>, years: RangeInclusive<i32>) -> Result<Events, Error> {
+        let mut events: Events = vec![];
+        for year in years {
+            let dates = holidays(country, region, year).map_err(|message| Error::Setting {
+                name: String::from("holidays"),
+                message,
+            })?;
+            for (date, name) in dates {
+                events.push(Event {
+                    start: EventDateTime::Date(date),
+                    end: EventDateTime::Date(date),
+                    rrulesets: vec![],
+                    summary: name.to_string(),
+                    categories: vec![String::from(HOLIDAY_CATEGORY)],
+                });
+            }
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_easter_sunday() {
+        assert_eq!(easter_sunday(2024), date(2024, 3, 31));
+        assert_eq!(easter_sunday(2025), date(2025, 4, 20));
+        assert_eq!(easter_sunday(2026), date(2026, 4, 5));
+        assert_eq!(easter_sunday(2038), date(2038, 4, 25));
+        assert_eq!(easter_sunday(1818), date(1818, 3, 22));
+    }
+    #[test]
+    fn test_holidays_at() {
+        let holidays = holidays("at", None, 2025).unwrap();
+        assert_eq!(holidays.len(), 13);
+        assert!(holidays.contains(&(date(2025, 6, 19), "Fronleichnam")));
+        assert!(holidays.contains(&(date(2025, 10, 26), "Nationalfeiertag")));
+    }
+    #[test]
+    fn test_holidays_de_regions() {
+        assert_eq!(holidays("DE", None, 2025).unwrap().len(), 9);
+        let sachsen = holidays("DE", Some("DE-SN"), 2025).unwrap();
+        assert!(sachsen.contains(&(date(2025, 11, 19), "Buß- und Bettag")));
+        assert!(sachsen.contains(&(date(2025, 10, 31), "Reformationstag")));
+        let berlin = holidays("DE", Some("BE"), 2018).unwrap();
+        assert!(!berlin.iter().any(|(_, name)| *name == "Internationaler Frauentag"));
+        assert!(holidays("DE", Some("XX"), 2025).is_err());
+    }
+    #[test]
+    fn test_holidays_us() {
+        let holidays = holidays("US", None, 2025).unwrap();
+        assert!(holidays.contains(&(date(2025, 1, 20), "Martin Luther King Jr. Day")));
+        assert!(holidays.contains(&(date(2025, 5, 26), "Memorial Day")));
+        assert!(holidays.contains(&(date(2025, 11, 27), "Thanksgiving Day")));
+    }
+    #[test]
+    fn test_holidays_unknown_country() {
+        assert!(holidays("XX", None, 2025).is_err());
+    }
+    #[test]
+    fn test_events_from_holidays() {
+        let events = Events::from_holidays("AT", None, 2025..=2026).unwrap();
+        assert_eq!(events.len(), 26);
+        assert_eq!(events[0].categories, vec![HOLIDAY_CATEGORY]);
+    }
+}
@@ -2,12 +2,13 @@
 //
 // SPDX-License-Identifier: MIT
 
+use crate::error::Error;
 use crate::events::{Event, EventDateTime, Events};
 use chrono::Duration;
 use icalendar::{Calendar, CalendarDateTime, Component, DatePerhapsTime, Event as IcalendarEvent};
 use rrule::RRuleSet;
 use std::cmp::max;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
 impl From<icalendar::DatePerhapsTime> for EventDateTime {
     fn from(dateperhapstime: icalendar::DatePerhapsTime) -> Self {
@@ -65,6 +66,7 @@ impl TryFrom<&IcalendarEvent> for Event {
                 end,
                 rrulesets,
                 summary: event.get_summary().unwrap_or_default().to_string(),
+                categories: categories(event),
             })
         } else {
             Err("Could not parse ical event.")
@@ -72,52 +74,66 @@ impl TryFrom<&IcalendarEvent> for Event {
     }
 }
 
+/// The categories of `event`, from all its CATEGORIES properties.
+fn categories(event: &IcalendarEvent) -> Vec<String> {
+    event
+        .multi_properties()
+        .get("CATEGORIES")
+        .into_iter()
+        .flatten()
+        .flat_map(|property| property.value().split(','))
+        .map(|category| category.trim().to_string())
+        .filter(|category| !category.is_empty())
+        .collect()
+}
+
 pub trait ReadFromIcsFile {
-    fn read_from_ics_file(filepath: &str) -> Events;
+    fn read_from_ics_file(filepath: &str, warnings: &mut Vec<Error>) -> Result<Events, Error>;
 }
 
 impl ReadFromIcsFile for Events {
-    fn read_from_ics_file(filepath: &str) -> Events {
-        let mut events: Events = vec![];
-        let mut filepaths: Vec<PathBuf> = vec![];
-
+    /// Read the events from the ics file `filepath`. If `filepath` is a
+    /// directory, all the `.ics` files in it are read. Files that can not
+    /// be read or parsed are added to `warnings` and skipped.
+    fn read_from_ics_file(filepath: &str, warnings: &mut Vec<Error>) -> Result<Events, Error> {
         let path = Path::new(filepath);
         if path.is_dir() {
-            if let Ok(path) = path.read_dir() {
-                for entry in path.flatten() {
-                    if entry.path().is_file() {
-                        filepaths.push(entry.path());
+            let entries = path.read_dir().map_err(|e| Error::Ics {
+                path: path.to_path_buf(),
+                message: e.to_string(),
+            })?;
+            let mut events: Events = vec![];
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_file() && path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("ics")) {
+                    match read_calendar(&path) {
+                        Ok(mut x) => events.append(&mut x),
+                        Err(e) => warnings.push(e),
                     }
                 }
-            } else {
-                eprintln!("Could not read dir {}", filepath);
             }
+            Ok(events)
         } else {
-            filepaths.push(path.to_path_buf())
+            read_calendar(path)
         }
-
-        for filepath in filepaths.iter() {
-            if let Ok(contents) = std::fs::read_to_string(filepath) {
-                if let Ok(calendar) = contents.parse::<Calendar>() {
-                    for event in calendar
-                        .components
-                        .iter()
-                        .filter_map(|component| component.as_event())
-                        .collect::<Vec<&IcalendarEvent>>()
-                    {
-                        if let Ok(e) = Event::try_from(event) {
-                            events.push(e);
-                        }
-                    }
-                }
-            } else {
-                eprintln!("Could not read file {}", filepath.display());
-            }
-        }
-        events
     }
 }
 
+fn read_calendar(path: &Path) -> Result<Events, Error> {
+    let ics_error = |message: String| Error::Ics {
+        path: path.to_path_buf(),
+        message,
+    };
+    let contents = std::fs::read_to_string(path).map_err(|e| ics_error(e.to_string()))?;
+    let calendar = contents.parse::<Calendar>().map_err(ics_error)?;
+    Ok(calendar
+        .components
+        .iter()
+        .filter_map(|component| component.as_event())
+        .filter_map(|event| Event::try_from(event).ok())
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,23 +151,49 @@ mod tests {
         assert!(Event::try_from(&icalevent).is_err());
     }
     #[test]
+    fn test_icalevent_categories() {
+        let mut icalevent = IcalendarEvent::default();
+        icalevent.add_property("DTSTART", "19700101T010130");
+        icalevent.add_multi_property("CATEGORIES", "Birthday,Family");
+        icalevent.add_multi_property("CATEGORIES", "Party");
+        assert_eq!(Event::try_from(&icalevent).unwrap().categories, vec!["Birthday", "Family", "Party"]);
+    }
+    #[test]
     fn test_read_from_ics_file() {
         let filename = "foobar.ics";
-        assert!(Events::read_from_ics_file(filename).is_empty());
+        assert!(Events::read_from_ics_file(filename, &mut vec![]).is_err());
+    }
+    #[test]
+    fn test_read_from_invalid_ics_file() {
+        let filename = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+        assert!(matches!(Events::read_from_ics_file(filename, &mut vec![]), Err(Error::Ics { .. })));
     }
     #[test]
     fn test_read_from_dir() {
         let filename = "/tmp";
-        assert!(Events::read_from_ics_file(filename).is_empty());
+        assert!(Events::read_from_ics_file(filename, &mut vec![]).unwrap().is_empty());
     }
     #[test]
     fn test_read_from_dir_nor() {
         let filename = "/root";
-        assert!(Events::read_from_ics_file(filename).is_empty());
+        assert!(Events::read_from_ics_file(filename, &mut vec![]).unwrap_or_default().is_empty());
+    }
+    #[test]
+    fn test_read_from_dir_with_broken_file() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = tempdir.path();
+        std::fs::copy(concat!(env!("CARGO_MANIFEST_DIR"), "/data/carl.ics"), dir.join("carl.ics")).unwrap();
+        std::fs::write(dir.join("broken.ics"), "BEGIN:VCALENDAR\nfoo").unwrap();
+        std::fs::write(dir.join("displayname"), "carl").unwrap();
+        let mut warnings = vec![];
+        let events = Events::read_from_ics_file(&dir.display().to_string(), &mut warnings).unwrap();
+        assert_eq!(events.len(), 23);
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(&warnings[0], Error::Ics { path, .. } if path.ends_with("broken.ics")));
     }
     #[test]
     fn test_read_from_carl_ics() {
         let filename = concat!(env!("CARGO_MANIFEST_DIR"), "/data/carl.ics");
-        assert_eq!(Events::read_from_ics_file(filename).len(), 21);
+        assert_eq!(Events::read_from_ics_file(filename, &mut vec![]).unwrap().len(), 23);
     }
 }
@@ -2,7 +2,11 @@
 //
 // SPDX-License-Identifier: MIT
 
+mod astronomy;
+mod holidays;
 mod ics;
+pub use astronomy::FromAstronomy;
+pub use holidays::{holidays, FromHolidays, HOLIDAY_CATEGORY};
 pub use ics::ReadFromIcsFile;
 
 use crate::config::Style;
@@ -27,6 +31,13 @@ impl EventDateTime {
             EventDateTime::DateTime { date_time, .. } => date_time.date(),
         }
     }
+
+    pub fn time(self) -> Option<NaiveTime> {
+        match self {
+            EventDateTime::Date(_) => None,
+            EventDateTime::DateTime { date_time, .. } => Some(date_time.time()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +47,27 @@ pub struct EventInstance {
     pub style: Style,
 }
 
+impl EventInstance {
+    /// The time the instance starts at, or `None` if this is an all-day
+    /// instance. Days following the first day of a non-recurring
+    /// multi-day event are treated as all-day instances.
+    pub fn start_time(&self) -> Option<NaiveTime> {
+        if self.event.rrulesets.is_empty() && self.date != self.event.start.date() {
+            return None;
+        }
+        self.event.start.time()
+    }
+
+    /// The time the instance ends at, if it ends on the day it starts.
+    pub fn end_time(&self) -> Option<NaiveTime> {
+        self.start_time()?;
+        if self.event.start.date() != self.event.end.date() {
+            return None;
+        }
+        self.event.end.time()
+    }
+}
+
 pub type EventInstances = Vec<EventInstance>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,11 +76,22 @@ pub struct Event {
     pub end: EventDateTime,
     pub rrulesets: Vec<RRuleSet>,
     pub summary: String,
+    pub categories: Vec<String>,
 }
 
 impl Event {
+    /// Whether `pattern` is contained in the summary, ignoring the case.
+    pub fn matches_text(&self, pattern: &str) -> bool {
+        self.summary.to_lowercase().contains(&pattern.to_lowercase())
+    }
+
+    /// Whether the event has the category `category`, ignoring the case.
+    pub fn has_category(&self, category: &str) -> bool {
+        self.categories.iter().any(|c| c.to_lowercase() == category.to_lowercase())
+    }
+
     pub fn instances(&self, start: &NaiveDate, end: &NaiveDate, style: &Style) -> EventInstances {
-        let timezone: Tz = Local::now().timezone().into();
+        let timezone: Tz = Tz::LOCAL;
         let before = timezone
             .with_ymd_and_hms(end.year(), end.month(), end.day(), 23, 59, 59)
             .unwrap();
@@ -80,12 +123,10 @@ impl Event {
                 }
             }
         } else {
+            // A timespan of n days can contain n + 1 instances of a daily rule
+            let limit = u16::try_from(duration.num_days() + 1).unwrap_or(u16::MAX);
             for rruleset in &self.rrulesets {
-                let ruleset = rruleset
-                    .clone()
-                    .before(before)
-                    .after(after)
-                    .all(duration.num_days() as u16);
+                let ruleset = rruleset.clone().before(before).after(after).all(limit);
                 eventinstances.append(
                     &mut ruleset
                         .dates
@@ -112,6 +153,7 @@ impl Default for Event {
             end: EventDateTime::Date(NaiveDate::default()),
             rrulesets: vec![],
             summary: String::from("Default Event"),
+            categories: vec![],
         }
     }
 }
@@ -127,6 +169,18 @@ mod tests {
         assert_eq!(event.start, EventDateTime::Date(date));
     }
     #[test]
+    fn test_event_matches() {
+        let event = Event {
+            summary: String::from("Birthday of Ada"),
+            categories: vec![String::from("Family")],
+            ..Default::default()
+        };
+        assert!(event.matches_text("birthday"));
+        assert!(!event.matches_text("party"));
+        assert!(event.has_category("family"));
+        assert!(!event.has_category("fam"));
+    }
+    #[test]
     fn test_event_get_end_date_case2() {
         let date = NaiveDate::default();
         let event = Event {
@@ -137,6 +191,64 @@ mod tests {
         assert_eq!(event.end.date(), date);
     }
     #[test]
+    fn test_event_instance_times() {
+        let start = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        let event = Event {
+            start: EventDateTime::DateTime {
+                date_time: start.and_hms_opt(9, 30, 0).unwrap(),
+                offset: None,
+            },
+            end: EventDateTime::DateTime {
+                date_time: start.and_hms_opt(10, 15, 0).unwrap(),
+                offset: None,
+            },
+            ..Default::default()
+        };
+        let instances = event.instances(&start, &start, &Style::default());
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0].start_time(), NaiveTime::from_hms_opt(9, 30, 0));
+        assert_eq!(instances[0].end_time(), NaiveTime::from_hms_opt(10, 15, 0));
+    }
+    #[test]
+    fn test_event_instance_allday() {
+        let date = NaiveDate::default();
+        let instance = EventInstance {
+            date,
+            event: Event::default(),
+            style: Style::default(),
+        };
+        assert_eq!(instance.start_time(), None);
+        assert_eq!(instance.end_time(), None);
+    }
+    #[test]
+    fn test_event_instances_recurring_every_day() {
+        // A timespan of n days can contain n instances, also if it is a
+        // single day like in the day view
+        let start = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+        let event = Event {
+            start: EventDateTime::Date(start),
+            end: EventDateTime::Date(start),
+            rrulesets: vec!["DTSTART;VALUE=DATE:20261012\nRRULE:FREQ=DAILY".parse().unwrap()],
+            ..Default::default()
+        };
+        let style = Style::default();
+        assert_eq!(event.instances(&start, &start, &style).len(), 1);
+        assert_eq!(event.instances(&start, &(start + Duration::days(6)), &style).len(), 7);
+    }
+    #[test]
+    fn test_event_instances_long_timespan() {
+        // The number of days does not fit into the limit of the rrule crate
+        let start = NaiveDate::from_ymd_opt(1900, 1, 1).unwrap();
+        let event = Event {
+            start: EventDateTime::Date(start),
+            end: EventDateTime::Date(start),
+            rrulesets: vec!["DTSTART;VALUE=DATE:19000101\nRRULE:FREQ=WEEKLY".parse().unwrap()],
+            ..Default::default()
+        };
+        let end = start + Duration::days(i64::from(u16::MAX));
+        assert_eq!(event.instances(&start, &end, &Style::default()).len(), 9363);
+    }
+    #[test]
     fn test_event_get_end_date_case3() {
         let date = NaiveDate::default();
         let event = Event {
@@ -0,0 +1,153 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+//! The interactive mode: a full-screen view of the calendar in which a
+//! date can be selected using the keyboard. The calendar and the agenda
+//! of the selected date are rendered with the templates, just like the
+//! static output.
+
+use crate::calendar::Calendar;
+use crate::cli::Action;
+use crate::context::Context;
+use crate::error::Error;
+use crate::template::render;
+use chrono::Duration;
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::io::{self, IsTerminal, Write};
+
+const HELP: &str = "←↓↑→ day  PgUp/PgDn month  t today  q quit";
+
+/// What to do after a key was pressed.
+#[derive(Debug, PartialEq, Eq)]
+enum Step {
+    Select(chrono::NaiveDate),
+    Quit,
+}
+
+/// Switches the terminal to raw mode and the alternate screen and back
+/// again when it is dropped, also if rendering fails.
+struct Screen;
+
+impl Screen {
+    fn enter() -> io::Result<Screen> {
+        terminal::enable_raw_mode()?;
+        if let Err(e) = execute!(io::stdout(), EnterAlternateScreen, Hide) {
+            let _ = terminal::disable_raw_mode();
+            return Err(e);
+        }
+        Ok(Screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Run the interactive mode until it is quit.
+#[cfg(not(tarpaulin_include))]
+pub fn run(mut ctx: Context) -> Result<(), Error> {
+    if !io::stdout().is_terminal() {
+        return Err(Error::Usage(String::from("--interactive needs a terminal")));
+    }
+    // The agenda is shown for the selected date only
+    ctx.opts.action.agenda = false;
+    let mut date = ctx.usersetdate;
+    let _screen = Screen::enter()?;
+    loop {
+        ctx.set_date(date)?;
+        draw(&mut ctx)?;
+        let step = match event::read()? {
+            Event::Key(key) => handle_key(key, date, ctx.today, &ctx.calendar),
+            _ => None,
+        };
+        match step {
+            Some(Step::Select(selected)) => date = selected,
+            Some(Step::Quit) => return Ok(()),
+            None => (),
+        }
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+fn draw(ctx: &mut Context) -> Result<(), Error> {
+    let calendar = render(ctx)?;
+    let agenda = render_agenda(ctx)?;
+    let frame = format!("{}{}\n{}", calendar, agenda, HELP);
+    let mut stdout = io::stdout();
+    queue!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+    // In raw mode a newline does not move the cursor to the start of the line
+    write!(stdout, "{}", frame.replace('\n', "\r\n"))?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Render the agenda of the selected date.
+fn render_agenda(ctx: &mut Context) -> Result<String, Error> {
+    let action = std::mem::replace(&mut ctx.opts.action, Action { agenda: true, ..Action::default() });
+    let span = (ctx.begin, ctx.end);
+    (ctx.begin, ctx.end) = (ctx.usersetdate, ctx.usersetdate);
+    let agenda = render(ctx);
+    ctx.opts.action = action;
+    (ctx.begin, ctx.end) = span;
+    agenda
+}
+
+fn handle_key(key: KeyEvent, date: chrono::NaiveDate, today: chrono::NaiveDate, calendar: &Calendar) -> Option<Step> {
+    if key.kind != KeyEventKind::Press {
+        return None;
+    }
+    let step = match key.code {
+        KeyCode::Left => Step::Select(date - Duration::days(1)),
+        KeyCode::Right => Step::Select(date + Duration::days(1)),
+        KeyCode::Up => Step::Select(date - Duration::weeks(1)),
+        KeyCode::Down => Step::Select(date + Duration::weeks(1)),
+        KeyCode::PageUp => Step::Select(calendar.add_months(date, -1)),
+        KeyCode::PageDown => Step::Select(calendar.add_months(date, 1)),
+        KeyCode::Char('t') => Step::Select(today),
+        KeyCode::Char('q') | KeyCode::Esc => Step::Quit,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Step::Quit,
+        _ => return None,
+    };
+    Some(step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn press(code: KeyCode) -> Option<Step> {
+        handle_key(KeyEvent::from(code), date(2025, 1, 31), date(2025, 9, 9), &Calendar::Gregorian)
+    }
+
+    #[test]
+    fn test_handle_key() {
+        assert_eq!(press(KeyCode::Left), Some(Step::Select(date(2025, 1, 30))));
+        assert_eq!(press(KeyCode::Down), Some(Step::Select(date(2025, 2, 7))));
+        assert_eq!(press(KeyCode::PageDown), Some(Step::Select(date(2025, 2, 28))));
+        assert_eq!(press(KeyCode::PageUp), Some(Step::Select(date(2024, 12, 31))));
+        assert_eq!(press(KeyCode::Char('t')), Some(Step::Select(date(2025, 9, 9))));
+        assert_eq!(press(KeyCode::Esc), Some(Step::Quit));
+        assert_eq!(press(KeyCode::Char('x')), None);
+    }
+    #[test]
+    fn test_render_agenda() {
+        let mut ctx = Context::default();
+        ctx.set_date(date(2025, 9, 9)).unwrap();
+        let agenda = render_agenda(&mut ctx).unwrap();
+        assert!(agenda.contains("Agenda:"));
+        assert!(!ctx.opts.action.agenda);
+        assert_eq!((ctx.begin, ctx.end), (date(2025, 9, 1), date(2025, 9, 30)));
+    }
+}
@@ -0,0 +1,39 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+//! `carl` is a calendar for the commandline. This library provides
+//! what the `carl` binary is built of: the [`Context`] with the
+//! configuration, the theme and the events read from ics files
+//! (including the expansion of recurring events), the rendering of the
+//! templates and the other outputs of `carl`, like the status bar.
+//!
+//! ```no_run
+//! let ctx = carl::Context::new().unwrap();
+//! print!("{}", carl::render(&ctx).unwrap());
+//! ```
+
+mod bar;
+mod calendar;
+mod check;
+mod cli;
+mod config;
+mod context;
+mod error;
+mod events;
+mod interactive;
+mod template;
+mod utils;
+mod watch;
+
+pub use bar::{render_bar, run_i3bar};
+pub use check::check;
+pub use cli::{Cli, Command};
+pub use config::{Config, Location, Theme};
+pub use context::Context;
+pub use error::Error;
+pub use events::{Event, EventDateTime, EventInstance, Events, ReadFromIcsFile};
+pub use interactive::run as run_interactive;
+pub use template::{render, render_upcoming};
+pub use utils::DateExtensions;
+pub use watch::run as run_watch;
@@ -3,7 +3,10 @@
 // SPDX-License-Identifier: MIT
 use chrono::Datelike;
 use chrono::NaiveDate;
-use minijinja::{Error, ErrorKind};
+use crate::calendar::{calendar_system, Calendar, CalendarDate};
+use crate::utils::DateExtensions;
+use minijinja::{context, Error, ErrorKind, Value};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 pub(crate) fn days_per_year(date: chrono::NaiveDate) -> u32 {
     if date.leap_year() {
@@ -29,3 +32,119 @@ pub(crate) fn percentage_of_year(value: &str) -> Result<f32, Error> {
     }
     Err(Error::new(ErrorKind::InvalidOperation, "not a date."))
 }
+
+pub(crate) fn week_number(value: &str, from_sunday: Option<bool>) -> Result<u32, Error> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.week_number(from_sunday.unwrap_or_default()));
+    }
+    Err(Error::new(ErrorKind::InvalidOperation, "not a date."))
+}
+
+pub(crate) fn format_date(value: &str, format: Option<&str>, locale: Option<chrono::Locale>, calendar: Calendar) -> Result<String, Error> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let day = calendar.date(date);
+        if (day.year, day.month, day.day) != (date.year(), date.month(), date.day()) {
+            let format = format.unwrap_or(if locale.is_some() { "%x" } else { "%Y-%m-%d" });
+            return Ok(format_calendar_date(date, day, format, locale, calendar));
+        }
+        // Without a format, use the date format of the locale, but stay
+        // with ISO dates for the C locale
+        return Ok(match (format, locale) {
+            (None, None) => date.format("%Y-%m-%d").to_string(),
+            (None, Some(locale)) => date.format_localized("%x", locale).to_string(),
+            (Some(format), None) => date.format(format).to_string(),
+            (Some(format), Some(locale)) => date.format_localized(format, locale).to_string(),
+        });
+    }
+    Err(Error::new(ErrorKind::InvalidOperation, "not a date."))
+}
+
+/// Format a date that is not a gregorian date in `calendar`: the day,
+/// the month and the year are taken from `day`, everything else (like
+/// the weekday) from `date`.
+fn format_calendar_date(date: NaiveDate, day: CalendarDate, format: &str, locale: Option<chrono::Locale>, calendar: Calendar) -> String {
+    let strftime = |date: NaiveDate, specifier: &str| match locale {
+        Some(locale) => date.format_localized(specifier, locale).to_string(),
+        None => date.format(specifier).to_string(),
+    };
+    // The first day of the month exists in both calendars
+    let month = NaiveDate::from_ymd_opt(day.year, day.month, 1).unwrap_or(date);
+    let mut ret = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            ret.push(c);
+            continue;
+        }
+        let mut specifier = String::from("%");
+        let mut conversion = chars.next();
+        let flag = conversion.filter(|c| matches!(c, '-' | '_' | '0'));
+        if let Some(flag) = flag {
+            specifier.push(flag);
+            conversion = chars.next();
+        }
+        let Some(conversion) = conversion else {
+            ret.push_str(&specifier);
+            break;
+        };
+        specifier.push(conversion);
+        match (conversion, flag) {
+            ('d' | 'e', Some('-')) => ret.push_str(&day.day.to_string()),
+            ('d', None | Some('0')) | ('e', Some('0')) => ret.push_str(&format!("{:02}", day.day)),
+            ('d' | 'e', _) => ret.push_str(&format!("{:>2}", day.day)),
+            ('j', _) => ret.push_str(&format!("{:03}", calendar.ordinal(date))),
+            ('m' | 'B' | 'b' | 'h' | 'Y' | 'C' | 'y', _) => ret.push_str(&strftime(month, &specifier)),
+            ('F', _) => ret.push_str(&format_calendar_date(date, day, "%Y-%m-%d", locale, calendar)),
+            ('D', _) => ret.push_str(&format_calendar_date(date, day, "%m/%d/%y", locale, calendar)),
+            ('x', _) => {
+                let format = locale.map_or("%m/%d/%y", |locale| pure_rust_locales::locale_match!(locale => LC_TIME::D_FMT));
+                ret.push_str(&format_calendar_date(date, day, format, locale, calendar));
+            }
+            _ => ret.push_str(&strftime(date, &specifier)),
+        }
+    }
+    ret
+}
+
+/// The date in the given calendar system or, without a calendar system,
+/// in the calendar used for the calendar grid. Returns none if the date
+/// is outside of the range of the calendar system.
+pub(crate) fn calendar_date(value: &str, system: Option<&str>, calendar: Calendar) -> Result<Value, Error> {
+    let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") else {
+        return Err(Error::new(ErrorKind::InvalidOperation, "not a date."));
+    };
+    let system = match system {
+        Some(name) => calendar_system(name).ok_or(Error::new(ErrorKind::InvalidOperation, format!("unknown calendar system {}.", name)))?,
+        None => Box::new(calendar),
+    };
+    Ok(match system.to_calendar_date(date) {
+        Some(day) => context! {
+            year => day.year,
+            month => day.month,
+            day => day.day,
+            month_name => system.month_name(day),
+        },
+        None => Value::from(()),
+    })
+}
+
+/// The number of columns the string takes up in the terminal.
+pub(crate) fn width(value: &str) -> usize {
+    value.width()
+}
+
+/// Cut the string to the given width in terminal columns and pad it
+/// with spaces if it is shorter.
+pub(crate) fn fit(value: &str, width: usize) -> String {
+    let mut ret = String::new();
+    let mut ret_width = 0;
+    for c in value.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if ret_width + char_width > width {
+            break;
+        }
+        ret.push(c);
+        ret_width += char_width;
+    }
+    ret + &" ".repeat(width - ret_width)
+}
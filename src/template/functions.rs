@@ -1,11 +1,14 @@
 // SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
 //
 // SPDX-License-Identifier: MIT
+use chrono::Timelike;
 use minijinja::value::ViaDeserialize;
 use minijinja::value::Value;
+use minijinja::context;
 use crate::events::EventInstance;
-use crate::utils::tostyle;
-use crate::config::StyleName;
+use crate::utils::{countdown as describe_countdown, daylight, tostyle, Daylight, MoonPhase, Season};
+use crate::config::{Location, StyleName};
+use minijinja::{Error, ErrorKind};
 
 pub(crate) fn reset_style() -> String {
     "\x1B[0m".to_string()
@@ -43,3 +46,98 @@ pub(crate) fn style_event(event: ViaDeserialize<EventInstance>) -> String {
 pub(crate) fn style(styles: ViaDeserialize<Vec<StyleName>>) -> String {
     tostyle(styles.to_vec()).render().to_string()
 }
+
+/// Sort the event instances of every date into all-day instances and
+/// instances that start in one of the 24 hours of the day.
+pub(crate) fn events_by_hour(event_instances: ViaDeserialize<Vec<EventInstance>>, dates: ViaDeserialize<Vec<chrono::NaiveDate>>) -> Value {
+    let mut schedules: Vec<Value> = vec![];
+    for date in dates.iter() {
+        let mut allday: Vec<Value> = vec![];
+        let mut hours: Vec<Vec<Value>> = vec![vec![]; 24];
+        for instance in event_instances.iter().filter(|instance| instance.date == *date) {
+            match instance.start_time() {
+                Some(start) => hours[start.hour() as usize].push(context! {
+                    start => start.format("%H:%M").to_string(),
+                    end => instance.end_time().map(|end| end.format("%H:%M").to_string()),
+                    instance => Value::from_serialize(instance),
+                }),
+                None => allday.push(Value::from_serialize(instance)),
+            }
+        }
+        schedules.push(context! { date, allday, hours });
+    }
+    Value::from(schedules)
+}
+
+/// The name of the moon phase on the given date, if there is one.
+pub(crate) fn moon_phase(date: ViaDeserialize<chrono::NaiveDate>) -> Option<String> {
+    crate::utils::moon_phase(*date).map(|phase| phase.to_string())
+}
+
+/// All the moon phases between two dates (including both dates).
+pub(crate) fn moon_phases(from: ViaDeserialize<chrono::NaiveDate>, to: ViaDeserialize<chrono::NaiveDate>) -> Value {
+    let phases: Vec<Value> = crate::utils::moon_phases(*from, *to)
+        .into_iter()
+        .map(|(time, phase): (_, MoonPhase)| context! {
+            date => time.date_naive(),
+            time => time.format("%H:%M").to_string(),
+            phase => phase,
+            name => phase.to_string(),
+        })
+        .collect();
+    Value::from(phases)
+}
+
+/// The name of the equinox or solstice on the given date, if there is
+/// one.
+pub(crate) fn season(date: ViaDeserialize<chrono::NaiveDate>) -> Option<String> {
+    crate::utils::season(*date).map(|season| season.to_string())
+}
+
+/// The equinoxes and solstices of the given year.
+pub(crate) fn seasons(year: i32) -> Value {
+    let seasons: Vec<Value> = crate::utils::seasons(year)
+        .into_iter()
+        .map(|(time, season): (_, Season)| context! {
+            date => time.date_naive(),
+            time => time.format("%H:%M").to_string(),
+            season => season,
+            name => season.to_string(),
+        })
+        .collect();
+    Value::from(seasons)
+}
+
+fn daylight_at(date: chrono::NaiveDate, location: Option<Location>) -> Result<Daylight, Error> {
+    let location = location.ok_or(Error::new(ErrorKind::InvalidOperation, "the location is not configured."))?;
+    Ok(daylight(date, location.lat, location.lon))
+}
+
+/// The time of the sunrise on the given date at the location, if the
+/// sun rises on that date.
+pub(crate) fn sunrise(date: ViaDeserialize<chrono::NaiveDate>, location: Option<Location>) -> Result<Option<String>, Error> {
+    Ok(daylight_at(*date, location)?.sunrise.map(|time| time.format("%H:%M").to_string()))
+}
+
+/// The time of the sunset on the given date at the location, if the
+/// sun sets on that date.
+pub(crate) fn sunset(date: ViaDeserialize<chrono::NaiveDate>, location: Option<Location>) -> Result<Option<String>, Error> {
+    Ok(daylight_at(*date, location)?.sunset.map(|time| time.format("%H:%M").to_string()))
+}
+
+/// The time between the sunrise and the sunset on the given date at the
+/// location, in hours and minutes.
+pub(crate) fn day_length(date: ViaDeserialize<chrono::NaiveDate>, location: Option<Location>) -> Result<String, Error> {
+    let minutes = daylight_at(*date, location)?.day_length.num_minutes();
+    Ok(format!("{}:{:02}", minutes / 60, minutes % 60))
+}
+
+/// The time from `now` until the given date and, optionally, the time
+/// (as `HH:MM`) in words, e.g. "in 3 days".
+pub(crate) fn countdown(date: ViaDeserialize<chrono::NaiveDate>, time: Option<&str>, now: chrono::NaiveDateTime) -> Result<String, Error> {
+    let time = match time {
+        Some(time) => Some(chrono::NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| Error::new(ErrorKind::InvalidOperation, "the time has to be given as HH:MM."))?),
+        None => None,
+    };
+    Ok(describe_countdown(now, *date, time))
+}
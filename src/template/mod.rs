@@ -4,3 +4,118 @@
 pub mod filters;
 pub mod objects;
 pub mod functions;
+
+use crate::context::Context;
+use crate::error::Error;
+use crate::utils::{combine_styles, tostyle, DateExtensions};
+use minijinja::{path_loader, Environment, context};
+use minijinja::syntax::SyntaxConfig;
+
+const DEFAULT_TEMPLATE: &str = "carl.tmpl";
+const UPCOMING_TEMPLATE: &str = "upcoming.tmpl";
+
+/// How many days `carl upcoming` looks ahead for events.
+const UPCOMING_LOOKAHEAD_DAYS: i64 = 366;
+
+/// Create the template environment with the embedded templates, the
+/// custom templates from the template directory and all our filters
+/// and functions.
+pub fn environment(ctx: &Context) -> Environment<'static> {
+    let mut env = Environment::new();
+    env.set_syntax(SyntaxConfig::builder()
+        .line_statement_prefix("#")
+        .line_comment_prefix("##")
+        .build()
+        .unwrap()
+    );
+    minijinja_embed::load_templates!(&mut env);
+
+    if let Some(path) = ctx.config.template() {
+        // We implement template overloading for the embedded
+        // templates by iterating through the embedded templates
+        // and checking if in the template path a template with
+        // the same filename exists. If We find such a template,
+        // we remove the embedded one from the environment.
+        let mut remove_templates: Vec<String> = vec![];
+        for (name, _) in env.templates() {
+            if path.join(name).exists() {
+                remove_templates.push(name.to_string());
+            }
+        }
+        remove_templates.iter().for_each(|name| env.remove_template(name));
+        env.set_loader(path_loader(path));
+    }
+
+    env.add_filter("days_in_year_left", filters::days_in_year_left);
+    env.add_filter("percentage_of_year", filters::percentage_of_year);
+    env.add_filter("week_number", filters::week_number);
+    let locale = ctx.locale;
+    let calendar = ctx.calendar;
+    env.add_filter("format_date", move |value: &str, format: Option<&str>| filters::format_date(value, format, locale, calendar));
+    env.add_filter("calendar_date", move |value: &str, system: Option<&str>| filters::calendar_date(value, system, calendar));
+    env.add_filter("width", filters::width);
+    env.add_filter("fit", filters::fit);
+    env.add_function("dates_to_columns", functions::dates_to_columns);
+    env.add_function("events_by_hour", functions::events_by_hour);
+    env.add_function("moon_phase", functions::moon_phase);
+    env.add_function("moon_phases", functions::moon_phases);
+    env.add_function("season", functions::season);
+    env.add_function("seasons", functions::seasons);
+    let location = ctx.config.location;
+    env.add_function("sunrise", move |date| functions::sunrise(date, location));
+    env.add_function("sunset", move |date| functions::sunset(date, location));
+    env.add_function("day_length", move |date| functions::day_length(date, location));
+    let now = ctx.now;
+    env.add_function("countdown", move |date, time: Option<&str>| functions::countdown(date, time, now));
+    env.add_function("reset_style", functions::reset_style);
+    env.add_function("style_event", functions::style_event);
+    env.add_function("style", functions::style);
+    minijinja_contrib::add_to_environment(&mut env);
+    env
+}
+
+/// Render the output of carl for the given context.
+pub fn render(ctx: &Context) -> Result<String, Error> {
+    let event_instances = ctx.event_instances();
+    let dates_per_month = ctx.begin.generate_dates_from_to(ctx.end, ctx.opts.sunday, &ctx.calendar);
+    let dates_in_range: Vec<chrono::NaiveDate> = ctx.begin.iter_days().take_while(|date| *date <= ctx.end).collect();
+
+    let weeknumber_style = tostyle(combine_styles(ctx.theme.weeknumber.clone(), &ctx.styletype));
+    let date_styler = objects::DateStyler::new(event_instances.clone(), ctx.usersetdate, ctx.specified_date, ctx.today, ctx.theme.clone(), ctx.styletype.clone(), ctx.calendar);
+    let template_context = context! {
+        cli => ctx.opts,
+        columns => ctx.columns,
+        dates_per_month => dates_per_month,
+        dates_in_range => dates_in_range,
+        event_instances => event_instances,
+        main_date => ctx.usersetdate,
+        secondary_calendar => ctx.secondary_calendar,
+        today => ctx.today,
+        style_date => minijinja::Value::from_object(date_styler),
+        weeknumber_style => weeknumber_style.render().to_string(),
+    };
+
+    Ok(environment(ctx).get_template(DEFAULT_TEMPLATE)?.render(template_context)?)
+}
+
+/// Render the next `count` event instances, which can be in the
+/// following months, with the time until they start.
+pub fn render_upcoming(ctx: &Context, count: u16) -> Result<String, Error> {
+    let upcoming_events: Vec<minijinja::Value> = ctx
+        .upcoming_event_instances(ctx.now, UPCOMING_LOOKAHEAD_DAYS)
+        .into_iter()
+        .take(count.into())
+        .map(|instance| context! {
+            date => instance.date,
+            time => instance.start_time().map(|time| time.format("%H:%M").to_string()),
+            instance => instance,
+        })
+        .collect();
+    let template_context = context! {
+        cli => ctx.opts,
+        upcoming_events => upcoming_events,
+        today => ctx.today,
+    };
+
+    Ok(environment(ctx).get_template(UPCOMING_TEMPLATE)?.render(template_context)?)
+}
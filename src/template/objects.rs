@@ -2,26 +2,61 @@
 //
 // SPDX-License-Identifier: MIT
 use std::sync::Arc;
+use crate::calendar::Calendar;
 use crate::events::EventInstance;
-use crate::config::{Theme, Style, StyleType};
-use crate::utils::tostyle;
-use crate::utils::DateExtensions;
+use crate::config::{Theme, Style, StyleName, StyleType};
+use crate::utils::{combine_styles, tostyle};
+use crate::utils::{DateExtensions, DateSpan};
 use chrono::NaiveDate;
 use minijinja::value::{Object, Value, from_args};
 
 
+/// Computes the style of a date from the date properties of the
+/// theme and from the events on that date.
 #[derive(Debug)]
-pub(crate) struct DateStyler {
+pub struct DateStyler {
     specified_date: Option<chrono::NaiveDate>,
     event_instances: Vec<EventInstance>,
     main_date: chrono::NaiveDate,
+    today: chrono::NaiveDate,
     theme: Theme,
     styletype: StyleType,
+    calendar: Calendar,
 }
 
 impl DateStyler {
-    pub(crate) fn new(event_instances: Vec<EventInstance>, main_date: chrono::NaiveDate, specified_date: Option<chrono::NaiveDate>, theme: Theme, styletype: StyleType) -> Self {
-        Self { event_instances, main_date, specified_date, theme, styletype }
+    pub fn new(event_instances: Vec<EventInstance>, main_date: chrono::NaiveDate, specified_date: Option<chrono::NaiveDate>, today: chrono::NaiveDate, theme: Theme, styletype: StyleType, calendar: Calendar) -> Self {
+        Self { event_instances, main_date, specified_date, today, theme, styletype, calendar }
+    }
+
+    /// The stylenames that apply to `date`, if it is displayed as part
+    /// of the month `month`.
+    pub fn stylenames(&self, date: chrono::NaiveDate, month: chrono::NaiveDate) -> Vec<StyleName> {
+        let month = DateSpan::new(self.calendar.first_day_of_month(month), self.calendar.last_day_of_month(month));
+        let mut matching_styles: Vec<Style> = self.theme
+            .date
+            .iter()
+            .filter(|datestyle| {
+                date.satisfy_all(
+                    month,
+                    self.main_date,
+                    self.specified_date,
+                    self.today,
+                    &self.event_instances,
+                    &datestyle.properties,
+                )
+            })
+            .cloned()
+            .map(|datestyle| datestyle.style)
+            .collect();
+
+        for instance in &self.event_instances {
+            if instance.date == date {
+                matching_styles.push(instance.style.clone());
+            }
+        }
+
+        combine_styles(matching_styles, &self.styletype)
     }
 }
 
@@ -30,39 +65,7 @@ impl Object for DateStyler {
         let (date, month, ): (&str, Option<&str>, ) = from_args(args)?;
         if let Ok(pdate) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
             let month = NaiveDate::parse_from_str(month.unwrap_or(date), "%Y-%m-%d").unwrap_or(pdate);
-            let mut matching_styles: Vec<Style> = self.theme
-                .date
-                .iter()
-                .filter(|datestyle| {
-                    pdate.satisfy_all(
-                        month.first_day_of_month(),
-                        self.main_date,
-                        self.specified_date,
-                        &self.event_instances,
-                        &datestyle.properties,
-                    )
-                })
-                .cloned()
-                .map(|datestyle| datestyle.style)
-                .collect();
-
-            for instance in &self.event_instances {
-                if instance.date == pdate {
-                    matching_styles.push(instance.style.clone());
-                }
-            }
-
-            matching_styles.retain(|style| {
-                style.styletype == self.styletype || style.styletype == StyleType::None
-            });
-
-            matching_styles.sort_by(|a, b| a.weight.cmp(&b.weight));
-            let mut stylenames = vec![];
-            for mut style in matching_styles {
-                stylenames.append(&mut style.stylenames);
-            }
-
-            return Ok(tostyle(stylenames).render().to_string().into());
+            return Ok(tostyle(self.stylenames(pdate, month)).render().to_string().into());
         }
         Ok(date.into())
     }
@@ -0,0 +1,273 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+//! Moon phases, equinoxes and solstices, computed using the algorithms
+//! from Jean Meeus, "Astronomical Algorithms" (chapters 27 and 49). The
+//! periodic terms of the planetary arguments are left out, so the times
+//! are accurate to a few minutes, which is more than enough to find the
+//! date.
+
+use chrono::prelude::*;
+use serde::Serialize;
+use std::fmt;
+
+/// The julian day of the unix epoch.
+const UNIX_EPOCH_JD: f64 = 2440587.5;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoonPhase {
+    NewMoon,
+    FirstQuarter,
+    FullMoon,
+    LastQuarter,
+}
+
+impl fmt::Display for MoonPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoonPhase::NewMoon => write!(f, "New Moon"),
+            MoonPhase::FirstQuarter => write!(f, "First Quarter"),
+            MoonPhase::FullMoon => write!(f, "Full Moon"),
+            MoonPhase::LastQuarter => write!(f, "Last Quarter"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Season {
+    MarchEquinox,
+    JuneSolstice,
+    SeptemberEquinox,
+    DecemberSolstice,
+}
+
+impl Season {
+    pub fn is_equinox(&self) -> bool {
+        matches!(self, Season::MarchEquinox | Season::SeptemberEquinox)
+    }
+
+    pub fn is_solstice(&self) -> bool {
+        !self.is_equinox()
+    }
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Season::MarchEquinox => write!(f, "March Equinox"),
+            Season::JuneSolstice => write!(f, "June Solstice"),
+            Season::SeptemberEquinox => write!(f, "September Equinox"),
+            Season::DecemberSolstice => write!(f, "December Solstice"),
+        }
+    }
+}
+
+fn sin(degrees: f64) -> f64 {
+    degrees.to_radians().sin()
+}
+
+fn cos(degrees: f64) -> f64 {
+    degrees.to_radians().cos()
+}
+
+fn from_julian_day(jd: f64) -> Option<DateTime<Local>> {
+    let seconds = ((jd - UNIX_EPOCH_JD) * 86400.0).round() as i64;
+    Some(DateTime::from_timestamp(seconds, 0)?.with_timezone(&Local))
+}
+
+/// The julian ephemeris day of the moon phase with the lunation number
+/// `k`. Integer values of `k` are new moons, `k + 0.25` first quarters,
+/// `k + 0.5` full moons and `k + 0.75` last quarters.
+fn moon_phase_jde(k: f64) -> f64 {
+    let t = k / 1236.85;
+    let jde = 2451550.09766 + 29.530588861 * k + 0.00015437 * t.powi(2) - 0.00000015 * t.powi(3) + 0.00000000073 * t.powi(4);
+    let e = 1.0 - 0.002516 * t - 0.0000074 * t.powi(2);
+    let m = 2.5534 + 29.1053567 * k - 0.0000014 * t.powi(2) - 0.00000011 * t.powi(3);
+    let mm = 201.5643 + 385.81693528 * k + 0.0107582 * t.powi(2) + 0.00001238 * t.powi(3) - 0.000000058 * t.powi(4);
+    let f = 160.7108 + 390.67050284 * k - 0.0016118 * t.powi(2) - 0.00000227 * t.powi(3) + 0.000000011 * t.powi(4);
+    let omega = 124.7746 - 1.56375588 * k + 0.0020672 * t.powi(2) + 0.00000215 * t.powi(3);
+
+    let fraction = k.rem_euclid(1.0);
+    let correction = if fraction < 0.1 || fraction > 0.4 && fraction < 0.6 {
+        let (a, b, c, d, e1) = if fraction < 0.1 {
+            (-0.4072, 0.17241, 0.01608, 0.01039, 0.00739)
+        } else {
+            (-0.40614, 0.17302, 0.01614, 0.01043, 0.00734)
+        };
+        a * sin(mm) + b * e * sin(m) + c * sin(2.0 * mm) + d * sin(2.0 * f) + e1 * e * sin(mm - m) - 0.00515 * e * sin(mm + m)
+            + 0.00209 * e * e * sin(2.0 * m)
+            - 0.00111 * sin(mm - 2.0 * f)
+            - 0.00057 * sin(mm + 2.0 * f)
+            + 0.00056 * e * sin(2.0 * mm + m)
+            - 0.00042 * sin(3.0 * mm)
+            + 0.00042 * e * sin(m + 2.0 * f)
+            + 0.00038 * e * sin(m - 2.0 * f)
+            - 0.00024 * e * sin(2.0 * mm - m)
+            - 0.00017 * sin(omega)
+    } else {
+        let w = 0.00306 - 0.00038 * e * cos(m) + 0.00026 * cos(mm) - 0.00002 * cos(mm - m) + 0.00002 * cos(mm + m) + 0.00002 * cos(2.0 * f);
+        let w = if fraction < 0.5 { w } else { -w };
+        -0.62801 * sin(mm) + 0.17172 * e * sin(m) - 0.01183 * e * sin(mm + m) + 0.00862 * sin(2.0 * mm) + 0.00804 * sin(2.0 * f)
+            + 0.00454 * e * sin(mm - m)
+            + 0.00204 * e * e * sin(2.0 * m)
+            - 0.0018 * sin(mm - 2.0 * f)
+            - 0.0007 * sin(mm + 2.0 * f)
+            - 0.0004 * sin(3.0 * mm)
+            - 0.00034 * e * sin(2.0 * mm - m)
+            + 0.00032 * e * sin(m + 2.0 * f)
+            + 0.00032 * e * sin(m - 2.0 * f)
+            - 0.00028 * e * e * sin(mm + 2.0 * m)
+            + 0.00027 * e * sin(2.0 * mm + m)
+            - 0.00017 * sin(omega)
+            + w
+    };
+    jde + correction
+}
+
+/// All the moon phases between `from` and `to` (including both dates),
+/// ordered by time.
+pub fn moon_phases(from: NaiveDate, to: NaiveDate) -> Vec<(DateTime<Local>, MoonPhase)> {
+    const PHASES: [MoonPhase; 4] = [MoonPhase::NewMoon, MoonPhase::FirstQuarter, MoonPhase::FullMoon, MoonPhase::LastQuarter];
+    let year = f64::from(from.year()) + f64::from(from.ordinal0()) / 365.25;
+    // Start one lunation early, the approximation of k is not exact
+    let mut k = ((year - 2000.0) * 12.3685).floor() - 1.0;
+    let mut phases = vec![];
+    loop {
+        for (quarter, phase) in PHASES.iter().enumerate() {
+            let Some(time) = from_julian_day(moon_phase_jde(k + quarter as f64 * 0.25)) else {
+                return phases;
+            };
+            if time.date_naive() > to {
+                return phases;
+            }
+            if time.date_naive() >= from {
+                phases.push((time, *phase));
+            }
+        }
+        k += 1.0;
+    }
+}
+
+/// The moon phase on `date`, if there is one.
+pub fn moon_phase(date: NaiveDate) -> Option<MoonPhase> {
+    moon_phases(date, date).first().map(|(_, phase)| *phase)
+}
+
+// The periodic terms A, B and C of the equinoxes and solstices
+const SEASON_TERMS: [(f64, f64, f64); 24] = [
+    (485.0, 324.96, 1934.136),
+    (203.0, 337.23, 32964.467),
+    (199.0, 342.08, 20.186),
+    (182.0, 27.85, 445267.112),
+    (156.0, 73.14, 45036.886),
+    (136.0, 171.52, 22518.443),
+    (77.0, 222.54, 65928.934),
+    (74.0, 296.72, 3034.906),
+    (70.0, 243.58, 9037.513),
+    (58.0, 119.81, 33718.147),
+    (52.0, 297.17, 150.678),
+    (50.0, 21.02, 2281.226),
+    (45.0, 247.54, 29929.562),
+    (44.0, 325.15, 31555.956),
+    (29.0, 60.93, 4443.417),
+    (18.0, 155.12, 67555.328),
+    (17.0, 288.79, 4562.452),
+    (16.0, 198.04, 62894.029),
+    (14.0, 199.76, 31436.921),
+    (12.0, 95.39, 14577.848),
+    (12.0, 287.11, 31931.756),
+    (12.0, 320.81, 34777.259),
+    (9.0, 227.73, 1222.114),
+    (8.0, 15.45, 16859.074),
+];
+
+fn season_jde(year: i32, season: Season) -> f64 {
+    let y = f64::from(year - 2000) / 1000.0;
+    let jde0 = match season {
+        Season::MarchEquinox => 2451623.80984 + 365242.37404 * y + 0.05169 * y.powi(2) - 0.00411 * y.powi(3) - 0.00057 * y.powi(4),
+        Season::JuneSolstice => 2451716.56767 + 365241.62603 * y + 0.00325 * y.powi(2) + 0.00888 * y.powi(3) - 0.0003 * y.powi(4),
+        Season::SeptemberEquinox => 2451810.21715 + 365242.01767 * y - 0.11575 * y.powi(2) + 0.00337 * y.powi(3) + 0.00078 * y.powi(4),
+        Season::DecemberSolstice => 2451900.05952 + 365242.74049 * y - 0.06223 * y.powi(2) - 0.00823 * y.powi(3) + 0.00032 * y.powi(4),
+    };
+    let t = (jde0 - 2451545.0) / 36525.0;
+    let w = 35999.373 * t - 2.47;
+    let lambda = 1.0 + 0.0334 * cos(w) + 0.0007 * cos(2.0 * w);
+    let s: f64 = SEASON_TERMS.iter().map(|(a, b, c)| a * cos(b + c * t)).sum();
+    jde0 + 0.00001 * s / lambda
+}
+
+/// The equinoxes and solstices of `year`, ordered by time.
+pub fn seasons(year: i32) -> Vec<(DateTime<Local>, Season)> {
+    [Season::MarchEquinox, Season::JuneSolstice, Season::SeptemberEquinox, Season::DecemberSolstice]
+        .into_iter()
+        .filter_map(|season| Some((from_julian_day(season_jde(year, season))?, season)))
+        .collect()
+}
+
+/// The equinox or solstice on `date`, if there is one.
+pub fn season(date: NaiveDate) -> Option<Season> {
+    seasons(date.year()).into_iter().find(|(time, _)| time.date_naive() == date).map(|(_, season)| season)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(jd: f64) -> DateTime<Utc> {
+        from_julian_day(jd).unwrap().with_timezone(&Utc)
+    }
+
+    fn assert_close(actual: DateTime<Utc>, expected: &str) {
+        let expected: DateTime<Utc> = expected.parse().unwrap();
+        assert!((actual - expected).num_minutes().abs() <= 5, "{} is not close to {}", actual, expected);
+    }
+
+    #[test]
+    fn test_moon_phase_jde() {
+        // Example 49.a of Astronomical Algorithms: the new moon of
+        // February 1977 (in dynamical time)
+        assert!((moon_phase_jde(-283.0) - 2443192.65118).abs() < 0.001);
+        assert_close(utc(moon_phase_jde(318.0)), "2025-09-21T19:54:00Z");
+        assert_close(utc(moon_phase_jde(317.5)), "2025-09-07T18:09:00Z");
+        assert_close(utc(moon_phase_jde(317.25)), "2025-08-31T06:25:00Z");
+        assert_close(utc(moon_phase_jde(317.75)), "2025-09-14T10:33:00Z");
+    }
+    #[test]
+    fn test_moon_phases() {
+        let from = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2025, 12, 31).unwrap();
+        let phases = moon_phases(from, to);
+        assert_eq!(phases.iter().filter(|(_, phase)| *phase == MoonPhase::FullMoon).count(), 12);
+        assert_eq!(phases.iter().filter(|(_, phase)| *phase == MoonPhase::NewMoon).count(), 12);
+        assert!(phases.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(phases.iter().all(|(time, _)| from <= time.date_naive() && time.date_naive() <= to));
+    }
+    #[test]
+    fn test_moon_phase() {
+        let (time, phase) = moon_phases(NaiveDate::from_ymd_opt(2025, 9, 1).unwrap(), NaiveDate::from_ymd_opt(2025, 9, 10).unwrap())[0];
+        assert_eq!(phase, MoonPhase::FullMoon);
+        assert_eq!(moon_phase(time.date_naive()), Some(MoonPhase::FullMoon));
+        assert_eq!(moon_phase(time.date_naive().succ_opt().unwrap().succ_opt().unwrap()), None);
+    }
+    #[test]
+    fn test_season_jde() {
+        // Example 27.a of Astronomical Algorithms: the june solstice of
+        // 1962 (in dynamical time)
+        assert!((season_jde(1962, Season::JuneSolstice) - 2437837.39245).abs() < 0.001);
+        let seasons = seasons(2025);
+        assert_eq!(seasons.len(), 4);
+        assert_close(seasons[0].0.with_timezone(&Utc), "2025-03-20T09:01:00Z");
+        assert_close(seasons[1].0.with_timezone(&Utc), "2025-06-21T02:42:00Z");
+        assert_close(seasons[2].0.with_timezone(&Utc), "2025-09-22T18:19:00Z");
+        assert_close(seasons[3].0.with_timezone(&Utc), "2025-12-21T15:03:00Z");
+    }
+    #[test]
+    fn test_season() {
+        let (time, _) = seasons(2025)[3];
+        assert_eq!(season(time.date_naive()), Some(Season::DecemberSolstice));
+        assert!(Season::DecemberSolstice.is_solstice());
+        assert!(Season::MarchEquinox.is_equinox());
+        assert_eq!(season(NaiveDate::from_ymd_opt(2025, 8, 1).unwrap()), None);
+    }
+}
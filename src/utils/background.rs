@@ -0,0 +1,148 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+//! Detection of the background color of the terminal, to choose between
+//! the dark and the light styles of a theme.
+
+use crate::config::StyleType;
+
+/// Detect if the terminal has a dark or a light background. The
+/// terminal is asked for its background color (using OSC 11), if it
+/// does not answer the `COLORFGBG` environment variable is used.
+pub fn detect_styletype() -> Option<StyleType> {
+    query_background_color()
+        .and_then(|response| parse_osc11_response(&response))
+        .or_else(|| std::env::var("COLORFGBG").ok().and_then(|value| parse_colorfgbg(&value)))
+}
+
+/// Parse the answer of a terminal to the OSC 11 query, which looks like
+/// `ESC ] 11 ; rgb:RRRR/GGGG/BBBB BEL`, with one to four hex digits per
+/// color.
+pub fn parse_osc11_response(response: &str) -> Option<StyleType> {
+    let start = response.find("rgb:")? + 4;
+    let end = response[start..].find(['\x07', '\x1b']).map_or(response.len(), |end| start + end);
+    let colors: Vec<f64> = response[start..end]
+        .split('/')
+        .map(|color| {
+            if color.is_empty() || color.len() > 4 {
+                return None;
+            }
+            let value = u32::from_str_radix(color, 16).ok()?;
+            Some(f64::from(value) / f64::from((1u32 << (4 * color.len())) - 1))
+        })
+        .collect::<Option<Vec<f64>>>()?;
+    let [r, g, b] = colors[..] else {
+        return None;
+    };
+    let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    Some(if luminance < 0.5 { StyleType::Dark } else { StyleType::Light })
+}
+
+/// Parse the `COLORFGBG` environment variable, which contains the
+/// foreground and the background color as numbers of the 16 color
+/// palette, e.g. `15;0`.
+pub fn parse_colorfgbg(value: &str) -> Option<StyleType> {
+    let background: u8 = value.rsplit(';').next()?.parse().ok()?;
+    Some(match background {
+        0..=6 | 8 => StyleType::Dark,
+        _ => StyleType::Light,
+    })
+}
+
+/// How long to wait for the terminal to answer.
+#[cfg(unix)]
+const QUERY_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(200);
+
+#[cfg(unix)]
+fn query_background_color() -> Option<String> {
+    use crossterm::terminal;
+    use rustix::event::{poll, PollFd, PollFlags, Timespec};
+    use std::io::{self, IsTerminal, Write};
+    use std::time::Instant;
+
+    // Only ask if the answer goes to carl and the query to the terminal
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return None;
+    }
+    // Switch off line buffering and echo, so the answer of the terminal
+    // can be read without it showing up
+    terminal::enable_raw_mode().ok()?;
+    // The device attributes are asked for after the background color.
+    // Terminals answer that query also if they do not support the first
+    // one, so its answer marks the end of the answers.
+    let mut stdout = io::stdout();
+    let stdin = io::stdin();
+    let mut response: Vec<u8> = vec![];
+    if stdout.write_all(b"\x1b]11;?\x1b\\\x1b[c").and_then(|_| stdout.flush()).is_ok() {
+        // Stdin is only read when it has input, so nothing is left
+        // reading it after the timeout. The file descriptor is read
+        // directly, without the buffer of `io::stdin()`, to not take
+        // more input than is available.
+        let deadline = Instant::now() + QUERY_TIMEOUT;
+        while !ends_with_device_attributes(&response) && response.len() < 256 {
+            let Some(timeout) = deadline
+                .checked_duration_since(Instant::now())
+                .and_then(|remaining| Timespec::try_from(remaining).ok())
+            else {
+                break;
+            };
+            let mut fds = [PollFd::new(&stdin, PollFlags::IN)];
+            if !matches!(poll(&mut fds, Some(&timeout)), Ok(ready) if ready > 0) {
+                break;
+            }
+            let mut buffer = [0u8; 64];
+            match rustix::io::read(&stdin, &mut buffer) {
+                Ok(read) if read > 0 => response.extend_from_slice(&buffer[..read]),
+                _ => break,
+            }
+        }
+    }
+    let _ = terminal::disable_raw_mode();
+    String::from_utf8(response).ok()
+}
+
+/// Whether `response` ends with the answer to the device attributes
+/// query, which looks like `ESC [ ? 62 ; 22 c`.
+#[cfg(unix)]
+fn ends_with_device_attributes(response: &[u8]) -> bool {
+    response.ends_with(b"c") && response.windows(3).any(|window| window == b"\x1b[?")
+}
+
+#[cfg(not(unix))]
+fn query_background_color() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_osc11_response() {
+        assert_eq!(parse_osc11_response("\x1b]11;rgb:0000/0000/0000\x07"), Some(StyleType::Dark));
+        assert_eq!(parse_osc11_response("\x1b]11;rgb:ffff/ffff/dddd\x1b\\"), Some(StyleType::Light));
+        assert_eq!(parse_osc11_response("\x1b]11;rgb:fd/f6/e3\x07"), Some(StyleType::Light));
+        assert_eq!(parse_osc11_response("\x1b]11;rgb:0/2/3\x07"), Some(StyleType::Dark));
+        assert_eq!(parse_osc11_response("\x1b]11;rgb:ffff/ffff/ffff\x1b\\\x1b[?62;22c"), Some(StyleType::Light));
+        assert_eq!(parse_osc11_response("\x1b]11;rgb:ffffffff/0/0\x07"), None);
+        assert_eq!(parse_osc11_response("\x1b]11;rgb://\x07"), None);
+        assert_eq!(parse_osc11_response("\x1b]11;foo\x07"), None);
+        assert_eq!(parse_osc11_response(""), None);
+    }
+    #[test]
+    #[cfg(unix)]
+    fn test_ends_with_device_attributes() {
+        assert!(ends_with_device_attributes(b"\x1b]11;rgb:0/0/0\x07\x1b[?62;22c"));
+        assert!(ends_with_device_attributes(b"\x1b[?1;2c"));
+        assert!(!ends_with_device_attributes(b"\x1b]11;rgb:cccc/cccc/cccc"));
+    }
+    #[test]
+    fn test_parse_colorfgbg() {
+        assert_eq!(parse_colorfgbg("15;0"), Some(StyleType::Dark));
+        assert_eq!(parse_colorfgbg("0;15"), Some(StyleType::Light));
+        assert_eq!(parse_colorfgbg("12;default;8"), Some(StyleType::Dark));
+        assert_eq!(parse_colorfgbg("0;7"), Some(StyleType::Light));
+        assert_eq!(parse_colorfgbg("default"), None);
+    }
+}
@@ -2,8 +2,10 @@
 //
 // SPDX-License-Identifier: MIT
 
-use crate::config::DateProperty;
-use crate::events::EventInstance;
+use crate::calendar::Calendar;
+use crate::config::{DateProperty, Parity};
+use crate::events::{EventInstance, HOLIDAY_CATEGORY};
+use crate::utils::{moon_phase, season, DateSpan, MoonPhase};
 use chrono::prelude::*;
 use chrono::Duration;
 use chrono::Months;
@@ -14,11 +16,13 @@ pub trait DateExtensions {
     fn first_day_of_year(&self) -> chrono::NaiveDate;
     fn last_day_of_year(&self) -> chrono::NaiveDate;
     fn first_day_of_next_month(&self) -> chrono::NaiveDate;
+    fn first_day_of_week(&self, from_sunday: bool) -> chrono::NaiveDate;
     fn first_day_of_week_before_first_day_of_month(&self, from_sunday: bool) -> chrono::NaiveDate;
     fn last_day_of_week_after_last_day_of_month(&self, from_sunday: bool) -> chrono::NaiveDate;
     fn month_full_week(&self, from_sunday: bool) -> Vec<chrono::NaiveDate>;
-    fn generate_dates_from_to(&self, end: chrono::NaiveDate, from_sunday: bool) -> Vec<Vec<chrono::NaiveDate>>;
-    fn satisfy_all(&self, firstdayofmonth: chrono::NaiveDate, maindate: chrono::NaiveDate, specified_date: Option<chrono::NaiveDate>, events: &[EventInstance], properties: &[DateProperty]) -> bool;
+    fn generate_dates_from_to(&self, end: chrono::NaiveDate, from_sunday: bool, calendar: &Calendar) -> Vec<Vec<chrono::NaiveDate>>;
+    fn week_number(&self, from_sunday: bool) -> u32;
+    fn satisfy_all(&self, month: DateSpan, maindate: chrono::NaiveDate, specified_date: Option<chrono::NaiveDate>, today: chrono::NaiveDate, events: &[EventInstance], properties: &[DateProperty]) -> bool;
 }
 
 impl DateExtensions for chrono::NaiveDate {
@@ -63,13 +67,17 @@ impl DateExtensions for chrono::NaiveDate {
         *self
     }
 
-    fn first_day_of_week_before_first_day_of_month(&self, from_sunday: bool) -> chrono::NaiveDate {
+    fn first_day_of_week(&self, from_sunday: bool) -> chrono::NaiveDate {
         let days_to_weekstart = if from_sunday {
-            self.first_day_of_month().weekday().num_days_from_sunday()
+            self.weekday().num_days_from_sunday()
         } else {
-            self.first_day_of_month().weekday().num_days_from_monday()
+            self.weekday().num_days_from_monday()
         };
-        self.first_day_of_month() - Duration::days(days_to_weekstart.into())
+        *self - Duration::days(days_to_weekstart.into())
+    }
+
+    fn first_day_of_week_before_first_day_of_month(&self, from_sunday: bool) -> chrono::NaiveDate {
+        self.first_day_of_month().first_day_of_week(from_sunday)
     }
 
     fn last_day_of_week_after_last_day_of_month(&self, from_sunday: bool) -> chrono::NaiveDate {
@@ -89,33 +97,46 @@ impl DateExtensions for chrono::NaiveDate {
     /// is true, the days Sunday, Monday, and Tuesday before the first of
     /// the month are also part of the resulting vector.
     fn month_full_week(&self, from_sunday: bool) -> Vec<chrono::NaiveDate> {
-        let mut month_v: Vec<chrono::NaiveDate> = vec![];
-        let mut date = self.first_day_of_month().first_day_of_week_before_first_day_of_month(from_sunday);
-        while date <= self.last_day_of_month().last_day_of_week_after_last_day_of_month(from_sunday) {
-            month_v.push(date);
-            date += Duration::days(1);
-        }
-        month_v
+        self.generate_dates_from_to(*self, from_sunday, &Calendar::Gregorian).remove(0)
     }
 
-    fn generate_dates_from_to(&self, end: chrono::NaiveDate, from_sunday: bool) -> Vec<Vec<chrono::NaiveDate>> {
+    /// Generate the dates of all the months of `calendar` from the
+    /// month of this date to the month of `end`, every month with full
+    /// weeks (see `month_full_week`).
+    fn generate_dates_from_to(&self, end: chrono::NaiveDate, from_sunday: bool, calendar: &Calendar) -> Vec<Vec<chrono::NaiveDate>> {
         let mut dates: Vec<Vec<chrono::NaiveDate>> = vec![];
-        let mut months = vec![self.first_day_of_month()];
-        while months.last().unwrap().first_day_of_next_month() <= end {
-            months.push(months.last().unwrap().first_day_of_next_month());
+        let mut month = calendar.first_day_of_month(*self);
+        while month <= end {
+            let last_day_of_month = calendar.last_day_of_month(month);
+            let mut date = month.first_day_of_week(from_sunday);
+            let last_day_of_week = last_day_of_month.first_day_of_week(from_sunday) + Duration::days(6);
+            let mut month_v: Vec<chrono::NaiveDate> = vec![];
+            while date <= last_day_of_week {
+                month_v.push(date);
+                date += Duration::days(1);
+            }
+            dates.push(month_v);
+            month = last_day_of_month + Duration::days(1);
         }
+        dates
+    }
 
-        for month in months {
-            let month_v = month.month_full_week(from_sunday);
-            dates.push(month_v)
+    /// Return the ISO week number of the date or, if `from_sunday` is
+    /// true, the US week number. US weeks start on Sunday and week one
+    /// is the week that contains the first of January.
+    fn week_number(&self, from_sunday: bool) -> u32 {
+        if from_sunday {
+            let saturday = *self + Duration::days((6 - self.weekday().num_days_from_sunday()).into());
+            saturday.ordinal().div_ceil(7)
+        } else {
+            self.iso_week().week()
         }
-        dates
     }
-    fn satisfy_all(&self, firstdayofmonth: chrono::NaiveDate, maindate: chrono::NaiveDate, specified_date: Option<chrono::NaiveDate>, events: &[EventInstance], properties: &[DateProperty]) -> bool {
-        let today: chrono::NaiveDate = Local::now().date_naive();
+
+    fn satisfy_all(&self, month: DateSpan, maindate: chrono::NaiveDate, specified_date: Option<chrono::NaiveDate>, today: chrono::NaiveDate, events: &[EventInstance], properties: &[DateProperty]) -> bool {
         properties.iter().all(|prop| match prop {
-            DateProperty::FirstDayOfMonth => *self == firstdayofmonth,
-            DateProperty::BeforeFirstDayOfMonth => *self < firstdayofmonth,
+            DateProperty::FirstDayOfMonth => *self == month.begin,
+            DateProperty::BeforeFirstDayOfMonth => *self < month.begin,
             DateProperty::BeforeCurrentDate => *self < maindate,
             DateProperty::CurrentDate => *self == maindate,
             DateProperty::AfterCurrentDate => *self > maindate,
@@ -131,8 +152,8 @@ impl DateExtensions for chrono::NaiveDate {
                 Some(x) => *self > x,
                 None => false,
             },
-            DateProperty::AfterLastDayOfMonth => *self > firstdayofmonth.last_day_of_month(),
-            DateProperty::LastDayOfMonth => *self == firstdayofmonth.last_day_of_month(),
+            DateProperty::AfterLastDayOfMonth => *self > month.end,
+            DateProperty::LastDayOfMonth => *self == month.end,
             DateProperty::IsEvent => events
                 .iter()
                 .any(|eventinstance| eventinstance.date == *self),
@@ -148,6 +169,30 @@ impl DateExtensions for chrono::NaiveDate {
             DateProperty::BeforeToday => *self < today,
             DateProperty::Today => *self == today,
             DateProperty::AfterToday => *self > today,
+            DateProperty::DayOfMonth(day) => self.day() == *day,
+            DateProperty::NthWeekday { n, weekday } => {
+                let nth = if *n < 0 {
+                    -(((self.last_day_of_month().day() - self.day()) / 7 + 1) as i8)
+                } else {
+                    ((self.day() - 1) / 7 + 1) as i8
+                };
+                self.weekday() == *weekday && nth == *n
+            }
+            DateProperty::DateRange { from, to } => from <= self && self <= to,
+            DateProperty::Month(month) => self.month() == *month,
+            DateProperty::WeekParity(parity) => match parity {
+                Parity::Odd => !self.iso_week().week().is_multiple_of(2),
+                Parity::Even => self.iso_week().week().is_multiple_of(2),
+            },
+            DateProperty::Holiday => events
+                .iter()
+                .any(|eventinstance| eventinstance.date == *self && eventinstance.event.categories.iter().any(|category| category == HOLIDAY_CATEGORY)),
+            DateProperty::NewMoon => moon_phase(*self) == Some(MoonPhase::NewMoon),
+            DateProperty::FirstQuarter => moon_phase(*self) == Some(MoonPhase::FirstQuarter),
+            DateProperty::FullMoon => moon_phase(*self) == Some(MoonPhase::FullMoon),
+            DateProperty::LastQuarter => moon_phase(*self) == Some(MoonPhase::LastQuarter),
+            DateProperty::Equinox => season(*self).is_some_and(|season| season.is_equinox()),
+            DateProperty::Solstice => season(*self).is_some_and(|season| season.is_solstice()),
         })
     }
 }
@@ -189,6 +234,12 @@ mod tests {
         assert_eq!(i, u.first_day_of_next_month());
     }
     #[test]
+    fn test_first_day_of_week() {
+        let d = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        assert_eq!(d.first_day_of_week(false), NaiveDate::from_ymd_opt(2026, 10, 12).unwrap());
+        assert_eq!(d.first_day_of_week(true), NaiveDate::from_ymd_opt(2026, 10, 11).unwrap());
+    }
+    #[test]
     fn test_first_day_of_week_before_first_day_of_month() {
         let i = NaiveDate::default() + Months::new(3) + Days::new(26);
         let u = NaiveDate::default() + Months::new(4);
@@ -212,4 +263,109 @@ mod tests {
         let u = NaiveDate::default() + Months::new(3);
         assert_eq!(i, u.last_day_of_week_after_last_day_of_month(true));
     }
+    #[test]
+    fn test_week_number() {
+        let d = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        assert_eq!(d.week_number(false), 42);
+        assert_eq!(d.week_number(true), 42);
+        // 2027-01-01 is a Friday: ISO week 53 of 2026, US week 1 of 2027
+        let d = NaiveDate::from_ymd_opt(2027, 1, 1).unwrap();
+        assert_eq!(d.week_number(false), 53);
+        assert_eq!(d.week_number(true), 1);
+        let d = NaiveDate::from_ymd_opt(2026, 12, 27).unwrap();
+        assert_eq!(d.week_number(true), 1);
+        let d = NaiveDate::from_ymd_opt(2026, 12, 26).unwrap();
+        assert_eq!(d.week_number(true), 52);
+    }
+    #[test]
+    fn test_satisfy_day_of_month() {
+        let d = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        let props = [DateProperty::DayOfMonth(15)];
+        assert!(d.satisfy_all(DateSpan::months(d, 0, 0, &Calendar::Gregorian), d, None, d, &[], &props));
+        assert!(!d.succ_opt().unwrap().satisfy_all(DateSpan::months(d, 0, 0, &Calendar::Gregorian), d, None, d, &[], &props));
+    }
+    #[test]
+    fn test_satisfy_nth_weekday() {
+        // October 2026: Fridays are the 2nd, 9th, 16th, 23rd and 30th
+        let first = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap();
+        let last_friday = [DateProperty::NthWeekday { n: -1, weekday: Weekday::Fri }];
+        let second_friday = [DateProperty::NthWeekday { n: 2, weekday: Weekday::Fri }];
+        let d30 = NaiveDate::from_ymd_opt(2026, 10, 30).unwrap();
+        let d23 = NaiveDate::from_ymd_opt(2026, 10, 23).unwrap();
+        let d9 = NaiveDate::from_ymd_opt(2026, 10, 9).unwrap();
+        assert!(d30.satisfy_all(DateSpan::months(first, 0, 0, &Calendar::Gregorian), first, None, first, &[], &last_friday));
+        assert!(!d23.satisfy_all(DateSpan::months(first, 0, 0, &Calendar::Gregorian), first, None, first, &[], &last_friday));
+        assert!(d9.satisfy_all(DateSpan::months(first, 0, 0, &Calendar::Gregorian), first, None, first, &[], &second_friday));
+        assert!(!d30.satisfy_all(DateSpan::months(first, 0, 0, &Calendar::Gregorian), first, None, first, &[], &second_friday));
+    }
+    #[test]
+    fn test_satisfy_date_range() {
+        let props = [DateProperty::DateRange {
+            from: NaiveDate::from_ymd_opt(2026, 12, 24).unwrap(),
+            to: NaiveDate::from_ymd_opt(2027, 1, 6).unwrap(),
+        }];
+        let inside = NaiveDate::from_ymd_opt(2027, 1, 6).unwrap();
+        let outside = NaiveDate::from_ymd_opt(2026, 12, 23).unwrap();
+        assert!(inside.satisfy_all(DateSpan::months(inside, 0, 0, &Calendar::Gregorian), inside, None, inside, &[], &props));
+        assert!(!outside.satisfy_all(DateSpan::months(outside, 0, 0, &Calendar::Gregorian), outside, None, outside, &[], &props));
+    }
+    #[test]
+    fn test_satisfy_month_and_week_parity() {
+        // 2026-10-15 is in ISO week 42
+        let d = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        let first = d.first_day_of_month();
+        assert!(d.satisfy_all(DateSpan::months(first, 0, 0, &Calendar::Gregorian), d, None, d, &[], &[DateProperty::Month(10)]));
+        assert!(!d.satisfy_all(DateSpan::months(first, 0, 0, &Calendar::Gregorian), d, None, d, &[], &[DateProperty::Month(11)]));
+        assert!(d.satisfy_all(DateSpan::months(first, 0, 0, &Calendar::Gregorian), d, None, d, &[], &[DateProperty::WeekParity(Parity::Even)]));
+        assert!(!d.satisfy_all(DateSpan::months(first, 0, 0, &Calendar::Gregorian), d, None, d, &[], &[DateProperty::WeekParity(Parity::Odd)]));
+    }
+    #[test]
+    fn test_satisfy_holiday() {
+        use crate::config::Style;
+        use crate::events::{Events, FromHolidays};
+        let d = NaiveDate::from_ymd_opt(2026, 10, 26).unwrap();
+        let first = d.first_day_of_month();
+        let holidays = Events::from_holidays("AT", None, 2026..=2026).unwrap();
+        let instances: Vec<EventInstance> = holidays.iter().flat_map(|event| event.instances(&first, &first.last_day_of_month(), &Style::default())).collect();
+        assert!(d.satisfy_all(DateSpan::months(first, 0, 0, &Calendar::Gregorian), d, None, d, &instances, &[DateProperty::Holiday]));
+        assert!(!d.succ_opt().unwrap().satisfy_all(DateSpan::months(first, 0, 0, &Calendar::Gregorian), d, None, d, &instances, &[DateProperty::Holiday]));
+    }
+    #[test]
+    fn test_satisfy_moon_and_season() {
+        use crate::utils::{moon_phases, seasons};
+        let d = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
+        for (time, phase) in moon_phases(d, d.last_day_of_month()) {
+            let date = time.date_naive();
+            let property = match phase {
+                MoonPhase::NewMoon => DateProperty::NewMoon,
+                MoonPhase::FirstQuarter => DateProperty::FirstQuarter,
+                MoonPhase::FullMoon => DateProperty::FullMoon,
+                MoonPhase::LastQuarter => DateProperty::LastQuarter,
+            };
+            assert!(date.satisfy_all(DateSpan::months(d, 0, 0, &Calendar::Gregorian), d, None, d, &[], std::slice::from_ref(&property)));
+            assert!(!date.succ_opt().unwrap().satisfy_all(DateSpan::months(d, 0, 0, &Calendar::Gregorian), d, None, d, &[], &[property]));
+        }
+        let (equinox, _) = seasons(2025)[2];
+        assert!(equinox.date_naive().satisfy_all(DateSpan::months(d, 0, 0, &Calendar::Gregorian), d, None, d, &[], &[DateProperty::Equinox]));
+        assert!(!equinox.date_naive().satisfy_all(DateSpan::months(d, 0, 0, &Calendar::Gregorian), d, None, d, &[], &[DateProperty::Solstice]));
+    }
+    #[test]
+    fn test_generate_dates_from_to() {
+        let d = NaiveDate::from_ymd_opt(2025, 9, 9).unwrap();
+        let dates = d.generate_dates_from_to(NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(), false, &Calendar::Gregorian);
+        assert_eq!(dates.len(), 2);
+        assert_eq!(dates[0], d.month_full_week(false));
+        assert_eq!(dates[0].len(), 35);
+        assert_eq!(dates[1][0], NaiveDate::from_ymd_opt(2025, 9, 29).unwrap());
+    }
+    #[test]
+    fn test_generate_dates_from_to_reform() {
+        // September 1752 in the british empire: the 2nd was followed by the 14th
+        let d = NaiveDate::from_ymd_opt(1752, 9, 20).unwrap();
+        let dates = d.generate_dates_from_to(d, true, &Calendar::REFORM_1752);
+        assert_eq!(dates.len(), 1);
+        assert_eq!(dates[0].len(), 21);
+        assert_eq!(dates[0][0], NaiveDate::from_ymd_opt(1752, 9, 10).unwrap());
+        assert_eq!(dates[0][20], NaiveDate::from_ymd_opt(1752, 9, 30).unwrap());
+    }
 }
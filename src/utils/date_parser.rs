@@ -0,0 +1,318 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+use crate::calendar::{Calendar, CalendarDate, CalendarSystem};
+use chrono::prelude::*;
+use chrono::{Duration, Months};
+
+const MONTHNAMES: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// How precise the date passed on the commandline was.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatePrecision {
+    /// No date was passed, the date is today
+    Today,
+    Year,
+    Month,
+    Day,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsedDate {
+    pub date: chrono::NaiveDate,
+    pub precision: DatePrecision,
+}
+
+impl ParsedDate {
+    fn new(date: chrono::NaiveDate, precision: DatePrecision) -> Self {
+        ParsedDate { date, precision }
+    }
+}
+
+/// Parse the date values passed on the commandline. The values can be
+/// - numbers in the form `YYYY [MM [DD]]`
+/// - ISO dates (`2026-10-17` or `2026-10`)
+/// - month names, optionally with a day and a year (`oct 2026`, `17 october`)
+/// - `today`, `tomorrow`, `yesterday`
+/// - `next`, `last` or `this` followed by `day`, `week`, `month` or `year`
+/// - offsets like `+2w` or `-3d` (units are `d`, `w`, `m` and `y`)
+///
+/// Missing parts of the date are taken from `today`. Years, months and
+/// days are those of `calendar`.
+pub fn parse_date(values: &[String], today: chrono::NaiveDate, calendar: &Calendar) -> Result<ParsedDate, String> {
+    let tokens: Vec<String> = values
+        .iter()
+        .flat_map(|value| value.split_whitespace())
+        .map(|value| value.to_lowercase())
+        .collect();
+
+    match tokens.as_slice() {
+        [] => Ok(ParsedDate::new(today, DatePrecision::Today)),
+        [token] if token == "today" => Ok(ParsedDate::new(today, DatePrecision::Day)),
+        [token] if token == "tomorrow" => Ok(ParsedDate::new(today + Duration::days(1), DatePrecision::Day)),
+        [token] if token == "yesterday" => Ok(ParsedDate::new(today - Duration::days(1), DatePrecision::Day)),
+        [token] if token.starts_with('+') || token.starts_with('-') => parse_offset(token, today),
+        [token] if token.contains('-') => parse_iso(token, calendar),
+        [direction, unit] if ["next", "last", "this"].contains(&direction.as_str()) => {
+            let offset = match direction.as_str() {
+                "next" => "+1",
+                "last" => "-1",
+                _ => "+0",
+            };
+            let unit = match unit.as_str() {
+                "day" => "d",
+                "week" => "w",
+                "month" => "m",
+                "year" => "y",
+                _ => return Err(unparsable(values)),
+            };
+            parse_offset(&format!("{offset}{unit}"), today)
+        }
+        _ if tokens.iter().any(|token| month_from_name(token).is_some()) => parse_named(&tokens, today, values, calendar),
+        _ if tokens.iter().all(|token| token.chars().all(|c| c.is_ascii_digit())) => parse_numeric(&tokens, today, calendar),
+        _ => Err(unparsable(values)),
+    }
+}
+
+fn unparsable(values: &[String]) -> String {
+    format!("could not parse date value(s): {}", values.join(" "))
+}
+
+fn month_from_name(name: &str) -> Option<u32> {
+    if name.len() < 3 {
+        return None;
+    }
+    MONTHNAMES
+        .iter()
+        .position(|monthname| monthname.starts_with(name))
+        .map(|index| index as u32 + 1)
+}
+
+/// Create a date of `calendar` and check all the parts of it. If
+/// `clamp_day` is set, days that do not exist in the month are set to
+/// the last day of the month, or to the first day of the month if they
+/// were skipped by the calendar reform.
+fn from_ymd(year: i32, month: u32, day: u32, clamp_day: bool, calendar: &Calendar) -> Result<chrono::NaiveDate, String> {
+    if !(1..=9999).contains(&year) {
+        return Err(format!("illegal year value: use 1-9999: {}", year));
+    }
+    if !(1..=12).contains(&month) {
+        return Err(format!("illegal month value: use 1-12: {}", month));
+    }
+    let first_day = calendar.to_date(CalendarDate::new(year, month, 1)).unwrap();
+    let last_day = calendar.date(calendar.last_day_of_month(first_day)).day;
+    let day = if clamp_day { day.min(last_day) } else { day };
+    if !(1..=last_day).contains(&day) {
+        return Err(format!("illegal day value: use 1-{}: {}", last_day, day));
+    }
+    match calendar.to_date(CalendarDate::new(year, month, day)) {
+        Some(date) => Ok(date),
+        None if clamp_day => Ok(first_day),
+        None => Err(format!("illegal day value: {:04}-{:02}-{:02} was skipped by the calendar reform", year, month, day)),
+    }
+}
+
+fn parse_offset(token: &str, today: chrono::NaiveDate) -> Result<ParsedDate, String> {
+    let unit_start = token.char_indices().last().map_or(0, |(index, _)| index);
+    let (number, unit) = token.split_at(unit_start);
+    let Ok(number) = number.parse::<i32>() else {
+        return Err(format!("could not parse date offset: {}", token));
+    };
+    let months = |months: i32| {
+        if months < 0 {
+            today.checked_sub_months(Months::new(months.unsigned_abs()))
+        } else {
+            today.checked_add_months(Months::new(months as u32))
+        }
+    };
+    let parsed = match unit {
+        "d" => today
+            .checked_add_signed(Duration::days(number.into()))
+            .map(|date| ParsedDate::new(date, DatePrecision::Day)),
+        "w" => today
+            .checked_add_signed(Duration::weeks(number.into()))
+            .map(|date| ParsedDate::new(date, DatePrecision::Day)),
+        "m" => months(number).map(|date| ParsedDate::new(date, DatePrecision::Month)),
+        "y" => number.checked_mul(12).and_then(months).map(|date| ParsedDate::new(date, DatePrecision::Year)),
+        _ => {
+            return Err(format!(
+                "illegal date offset unit: use d, w, m or y: {}",
+                token
+            ))
+        }
+    };
+    match parsed {
+        Some(x) => from_ymd(x.date.year(), x.date.month(), x.date.day(), false, &Calendar::Gregorian).map(|_| x),
+        None => Err(format!("date offset out of range: {}", token)),
+    }
+}
+
+fn parse_iso(token: &str, calendar: &Calendar) -> Result<ParsedDate, String> {
+    let parts: Vec<&str> = token.split('-').collect();
+    let numbers: Vec<u32> = parts.iter().filter_map(|part| part.parse().ok()).collect();
+    if numbers.len() != parts.len() {
+        return Err(format!("illegal ISO date: use YYYY-MM-DD: {}", token));
+    }
+    match numbers.as_slice() {
+        [year, month] => from_ymd(*year as i32, *month, 1, false, calendar).map(|date| ParsedDate::new(date, DatePrecision::Month)),
+        [year, month, day] => from_ymd(*year as i32, *month, *day, false, calendar).map(|date| ParsedDate::new(date, DatePrecision::Day)),
+        _ => Err(format!("illegal ISO date: use YYYY-MM-DD: {}", token)),
+    }
+}
+
+/// Parse a date that contains the name of a month. Numbers up to 31 are
+/// interpreted as the day, bigger numbers as the year.
+fn parse_named(tokens: &[String], today: chrono::NaiveDate, values: &[String], calendar: &Calendar) -> Result<ParsedDate, String> {
+    let mut month: Option<u32> = None;
+    let mut day: Option<u32> = None;
+    let mut year: Option<i32> = None;
+    for token in tokens {
+        let token = token.trim_end_matches([',', '.']);
+        if let Some(x) = month_from_name(token) {
+            if month.replace(x).is_some() {
+                return Err(unparsable(values));
+            }
+        } else if let Ok(x) = token.parse::<u32>() {
+            if x > 31 && year.is_none() {
+                year = Some(x as i32);
+            } else if x <= 31 && day.is_none() {
+                day = Some(x);
+            } else {
+                return Err(unparsable(values));
+            }
+        } else {
+            return Err(unparsable(values));
+        }
+    }
+    let month = month.unwrap();
+    let year = year.unwrap_or(today.year());
+    match day {
+        Some(x) => from_ymd(year, month, x, false, calendar).map(|date| ParsedDate::new(date, DatePrecision::Day)),
+        None => from_ymd(year, month, today.day(), true, calendar).map(|date| ParsedDate::new(date, DatePrecision::Month)),
+    }
+}
+
+fn parse_numeric(tokens: &[String], today: chrono::NaiveDate, calendar: &Calendar) -> Result<ParsedDate, String> {
+    if tokens.len() > 3 {
+        return Err(format!(
+            "too many date values: use YYYY [MM [DD]]: {}",
+            tokens.join(" ")
+        ));
+    }
+    let year: i32 = tokens[0].parse().map_err(|x| {
+        format!("illegal year value: use 1-9999: {}", x)
+    })?;
+    let Some(month) = tokens.get(1) else {
+        return from_ymd(year, today.month(), today.day(), true, calendar).map(|date| ParsedDate::new(date, DatePrecision::Year));
+    };
+    let month: u32 = month.parse().map_err(|x| {
+        format!("illegal month value: use 1-12: {}", x)
+    })?;
+    let Some(day) = tokens.get(2) else {
+        return from_ymd(year, month, today.day(), true, calendar).map(|date| ParsedDate::new(date, DatePrecision::Month));
+    };
+    let day: u32 = day.parse().map_err(|x| {
+        format!("illegal day value: use 1-31: {}", x)
+    })?;
+    from_ymd(year, month, day, false, calendar).map(|date| ParsedDate::new(date, DatePrecision::Day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 31).unwrap()
+    }
+
+    fn parse(value: &str) -> Result<ParsedDate, String> {
+        let values: Vec<String> = value.split(' ').filter(|x| !x.is_empty()).map(String::from).collect();
+        parse_date(&values, today(), &Calendar::Gregorian)
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_parse_empty() {
+        assert_eq!(parse("").unwrap(), ParsedDate::new(today(), DatePrecision::Today));
+    }
+    #[test]
+    fn test_parse_keywords() {
+        assert_eq!(parse("today").unwrap(), ParsedDate::new(today(), DatePrecision::Day));
+        assert_eq!(parse("Tomorrow").unwrap().date, date(2026, 11, 1));
+        assert_eq!(parse("yesterday").unwrap().date, date(2026, 10, 30));
+    }
+    #[test]
+    fn test_parse_relative() {
+        assert_eq!(parse("next month").unwrap(), ParsedDate::new(date(2026, 11, 30), DatePrecision::Month));
+        assert_eq!(parse("last year").unwrap().date, date(2025, 10, 31));
+        assert_eq!(parse("next week").unwrap().date, date(2026, 11, 7));
+        assert_eq!(parse("this month").unwrap().date, today());
+        assert!(parse("next fortnight").is_err());
+    }
+    #[test]
+    fn test_parse_offsets() {
+        assert_eq!(parse("+2w").unwrap(), ParsedDate::new(date(2026, 11, 14), DatePrecision::Day));
+        assert_eq!(parse("-3d").unwrap(), ParsedDate::new(date(2026, 10, 28), DatePrecision::Day));
+        assert_eq!(parse("+4m").unwrap().date, date(2027, 2, 28));
+        assert!(parse("+2x").is_err());
+        assert!(parse("+w").is_err());
+        assert!(parse("+2ü").is_err());
+        assert!(parse("+999999999y").is_err());
+        assert!(parse("-999999999y").is_err());
+    }
+    #[test]
+    fn test_parse_iso() {
+        assert_eq!(parse("2026-10-17").unwrap(), ParsedDate::new(date(2026, 10, 17), DatePrecision::Day));
+        assert_eq!(parse("2026-02").unwrap(), ParsedDate::new(date(2026, 2, 1), DatePrecision::Month));
+        assert!(parse("2026-02-30").is_err());
+        assert!(parse("2026-1o-17").is_err());
+    }
+    #[test]
+    fn test_parse_monthnames() {
+        assert_eq!(parse("oct 2025").unwrap(), ParsedDate::new(date(2025, 10, 31), DatePrecision::Month));
+        assert_eq!(parse("feb").unwrap().date, date(2026, 2, 28));
+        assert_eq!(parse("17 October 2027").unwrap(), ParsedDate::new(date(2027, 10, 17), DatePrecision::Day));
+        assert_eq!(parse("Sept 3").unwrap().date, date(2026, 9, 3));
+        assert!(parse("ju").is_err());
+        assert!(parse("oct nov").is_err());
+        assert!(parse("oct 2026 2027").is_err());
+    }
+    #[test]
+    fn test_parse_numeric() {
+        assert_eq!(parse("2007").unwrap(), ParsedDate::new(date(2007, 10, 31), DatePrecision::Year));
+        assert_eq!(parse("2007 2").unwrap(), ParsedDate::new(date(2007, 2, 28), DatePrecision::Month));
+        assert_eq!(parse("2007 2 3").unwrap(), ParsedDate::new(date(2007, 2, 3), DatePrecision::Day));
+        assert!(parse("0").is_err());
+        assert!(parse("2007 0").is_err());
+        assert!(parse("2007 1 2 3").is_err());
+        assert!(parse("2007 foo").is_err());
+    }
+    #[test]
+    fn test_parse_reform() {
+        let values = |value: &str| value.split(' ').map(String::from).collect::<Vec<String>>();
+        let calendar = Calendar::REFORM_1752;
+        assert_eq!(parse_date(&values("1752 9 2"), today(), &calendar).unwrap().date, date(1752, 9, 13));
+        assert_eq!(parse_date(&values("1752-09-14"), today(), &calendar).unwrap().date, date(1752, 9, 14));
+        assert!(parse_date(&values("1752 9 5"), today(), &calendar).is_err());
+        assert_eq!(parse_date(&values("sep 1752"), today(), &calendar).unwrap().date, date(1752, 9, 30));
+        assert_eq!(parse_date(&values("1700 2 29"), today(), &calendar).unwrap().date, date(1700, 3, 11));
+        assert_eq!(parse_date(&values("tomorrow"), today(), &calendar).unwrap().date, date(2026, 11, 1));
+    }
+}
@@ -0,0 +1,99 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+use crate::calendar::Calendar;
+use crate::utils::DateExtensions;
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+/// A span of consecutive dates, `begin` and `end` are both part of the span.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateSpan {
+    pub begin: chrono::NaiveDate,
+    pub end: chrono::NaiveDate,
+}
+
+impl DateSpan {
+    pub fn new(begin: chrono::NaiveDate, end: chrono::NaiveDate) -> Self {
+        DateSpan { begin, end }
+    }
+
+    pub fn day(date: chrono::NaiveDate) -> Self {
+        DateSpan::new(date, date)
+    }
+
+    pub fn week(date: chrono::NaiveDate, from_sunday: bool) -> Self {
+        let begin = date.first_day_of_week(from_sunday);
+        DateSpan::new(begin, begin + Duration::days(6))
+    }
+
+    /// The month of `date` together with `before` months before it
+    /// and `after` months after it, using the months of `calendar`.
+    pub fn months(date: chrono::NaiveDate, before: u32, after: u32, calendar: &Calendar) -> Self {
+        let mut begin = calendar.first_day_of_month(date);
+        for _ in 0..before {
+            begin = calendar.first_day_of_month(begin - Duration::days(1));
+        }
+        let mut end = calendar.last_day_of_month(date);
+        for _ in 0..after {
+            end = calendar.last_day_of_month(end + Duration::days(1));
+        }
+        DateSpan::new(begin, end)
+    }
+
+    pub fn year(date: chrono::NaiveDate, calendar: &Calendar) -> Self {
+        DateSpan::new(calendar.first_day_of_year(date), calendar.last_day_of_year(date))
+    }
+
+    /// The number of months of `calendar` the span touches.
+    pub fn number_of_months(&self, calendar: &Calendar) -> u32 {
+        let (begin, end) = (calendar.date(self.begin), calendar.date(self.end));
+        let months = (end.year - begin.year) * 12 + end.month as i32 - begin.month as i32 + 1;
+        months.max(0) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_span_day() {
+        let span = DateSpan::day(date(2026, 10, 17));
+        assert_eq!(span.begin, span.end);
+        assert_eq!(span.number_of_months(&Calendar::Gregorian), 1);
+    }
+    #[test]
+    fn test_span_week() {
+        let span = DateSpan::week(date(2026, 10, 1), false);
+        assert_eq!(span, DateSpan::new(date(2026, 9, 28), date(2026, 10, 4)));
+        assert_eq!(span.number_of_months(&Calendar::Gregorian), 2);
+    }
+    #[test]
+    fn test_span_months() {
+        let span = DateSpan::months(date(2026, 1, 17), 2, 4, &Calendar::Gregorian);
+        assert_eq!(span, DateSpan::new(date(2025, 11, 1), date(2026, 5, 31)));
+        assert_eq!(span.number_of_months(&Calendar::Gregorian), 7);
+    }
+    #[test]
+    fn test_span_year() {
+        let span = DateSpan::year(date(2026, 10, 17), &Calendar::Gregorian);
+        assert_eq!(span, DateSpan::new(date(2026, 1, 1), date(2026, 12, 31)));
+        assert_eq!(span.number_of_months(&Calendar::Gregorian), 12);
+    }
+    #[test]
+    fn test_span_months_reform() {
+        let span = DateSpan::months(date(1752, 9, 20), 1, 1, &Calendar::REFORM_1752);
+        assert_eq!(span, DateSpan::new(date(1752, 8, 12), date(1752, 10, 31)));
+        assert_eq!(span.number_of_months(&Calendar::REFORM_1752), 3);
+        let span = DateSpan::months(date(2025, 9, 9), 0, 0, &Calendar::Julian);
+        assert_eq!(span, DateSpan::new(date(2025, 8, 14), date(2025, 9, 13)));
+        assert_eq!(span.number_of_months(&Calendar::Julian), 1);
+    }
+}
@@ -2,7 +2,8 @@
 //
 // SPDX-License-Identifier: MIT
 
-use crate::config::StyleName;
+use crate::config;
+use crate::config::{StyleName, StyleType};
 use anstyle::Ansi256Color;
 use anstyle::AnsiColor::*;
 use anstyle::RgbColor;
@@ -47,11 +48,90 @@ pub fn tostyle(styles: Vec<StyleName>) -> Style {
     style
 }
 
+/// Collect the stylenames of all the styles that apply to the given
+/// styletype, ordered by the weight of the styles.
+pub fn combine_styles(mut styles: Vec<config::Style>, styletype: &StyleType) -> Vec<StyleName> {
+    styles.retain(|style| style.styletype == *styletype || style.styletype == StyleType::None);
+    styles.sort_by_key(|style| style.weight);
+    let mut stylenames = vec![];
+    for mut style in styles {
+        stylenames.append(&mut style.stylenames);
+    }
+    stylenames
+}
+
+/// The time from `now` until `date` (and `time`, if given) in words,
+/// like "in 2h 15m", "tomorrow" or "3 days ago". Times less than a day
+/// away are given in hours and minutes, other times in days.
+pub fn countdown(now: chrono::NaiveDateTime, date: chrono::NaiveDate, time: Option<chrono::NaiveTime>) -> String {
+    if let Some(time) = time {
+        let minutes = (date.and_time(time) - now).num_minutes();
+        if minutes.abs() < 24 * 60 {
+            let (hours, minutes_left) = (minutes.abs() / 60, minutes.abs() % 60);
+            let duration = match (hours, minutes_left) {
+                (0, minutes) => format!("{}m", minutes),
+                (hours, 0) => format!("{}h", hours),
+                (hours, minutes) => format!("{}h {}m", hours, minutes),
+            };
+            return match minutes {
+                0 => String::from("now"),
+                1.. => format!("in {}", duration),
+                _ => format!("{} ago", duration),
+            };
+        }
+    }
+    match (date - now.date()).num_days() {
+        0 => String::from("today"),
+        1 => String::from("tomorrow"),
+        -1 => String::from("yesterday"),
+        days @ 2.. => format!("in {} days", days),
+        days => format!("{} days ago", -days),
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::{NaiveDate, NaiveTime};
+
+    #[test]
+    fn test_countdown() {
+        let day = NaiveDate::from_ymd_opt(2025, 9, 9).unwrap();
+        let now = day.and_hms_opt(11, 45, 0).unwrap();
+        let at = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0);
+        assert_eq!(countdown(now, day, at(14, 0)), "in 2h 15m");
+        assert_eq!(countdown(now, day, at(12, 0)), "in 15m");
+        assert_eq!(countdown(now, day, at(9, 45)), "2h ago");
+        assert_eq!(countdown(now, day, at(11, 45)), "now");
+        assert_eq!(countdown(now, day.succ_opt().unwrap(), at(9, 0)), "in 21h 15m");
+        assert_eq!(countdown(now, day, None), "today");
+        assert_eq!(countdown(now, NaiveDate::from_ymd_opt(2025, 9, 12).unwrap(), at(9, 0)), "in 3 days");
+        assert_eq!(countdown(now, NaiveDate::from_ymd_opt(2025, 9, 10).unwrap(), None), "tomorrow");
+        assert_eq!(countdown(now, NaiveDate::from_ymd_opt(2025, 9, 1).unwrap(), None), "8 days ago");
+    }
 
+    #[test]
+    fn test_combine_styles() {
+        let styles = vec![
+            config::Style {
+                stylenames: vec![StyleName::Bold],
+                weight: 2,
+                ..Default::default()
+            },
+            config::Style {
+                stylenames: vec![StyleName::FGRed],
+                styletype: StyleType::Dark,
+                ..Default::default()
+            },
+            config::Style {
+                stylenames: vec![StyleName::Italic],
+                weight: 1,
+                styletype: StyleType::Light,
+            },
+        ];
+        let stylenames = combine_styles(styles, &StyleType::Light);
+        assert_eq![tostyle(stylenames), Style::default().italic().bold()];
+    }
     #[test]
     fn test_to_style1() {
         let a = vec![StyleName::Bold, StyleName::FGBlack, StyleName::BGBlack];
@@ -0,0 +1,75 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+use chrono::Locale;
+
+/// Determine the locale used for the names of months and weekdays and
+/// for date formats: the `locale` setting or, if it is not set, the
+/// `LC_ALL`, `LC_TIME` or `LANG` environment variable. `None` means the
+/// `C` locale. An unknown locale in the environment is ignored, an
+/// unknown `locale` setting is an error.
+pub fn resolve_locale(setting: Option<&str>) -> Result<Option<Locale>, String> {
+    if let Some(name) = setting {
+        return parse_locale(name).ok_or(format!("unknown locale {}", name));
+    }
+    let name = ["LC_ALL", "LC_TIME", "LANG"]
+        .iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default();
+    Ok(parse_locale(&name).flatten())
+}
+
+/// Parse a locale name like `de_AT`, `de_AT.UTF-8` or `ca_ES.UTF-8@valencia`.
+/// Returns `Some(None)` for the `C` locale and `None` if the locale is
+/// unknown.
+pub fn parse_locale(name: &str) -> Option<Option<Locale>> {
+    let (name, modifier) = match name.split_once('@') {
+        Some((name, modifier)) => (name, Some(modifier)),
+        None => (name, None),
+    };
+    let name = name.split('.').next().unwrap_or_default();
+    if name.is_empty() || name == "C" || name == "POSIX" {
+        return Some(None);
+    }
+    modifier
+        .and_then(|modifier| Locale::try_from(format!("{}@{}", name, modifier).as_str()).ok())
+        .or_else(|| Locale::try_from(name).ok())
+        .map(Some)
+}
+
+/// If the weeks start with Sunday in the given locale.
+pub fn starts_with_sunday(locale: Locale) -> bool {
+    // The first weekday is counted from Sunday, it defaults to Sunday
+    matches!(pure_rust_locales::locale_match!(locale => LC_TIME::FIRST_WEEKDAY), None | Some(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!(parse_locale("de_AT"), Some(Some(Locale::de_AT)));
+        assert_eq!(parse_locale("de_AT.UTF-8"), Some(Some(Locale::de_AT)));
+        assert_eq!(parse_locale("ca_ES.UTF-8@valencia"), Some(Some(Locale::ca_ES_valencia)));
+        assert_eq!(parse_locale("de_DE@euro"), Some(Some(Locale::de_DE_euro)));
+        assert_eq!(parse_locale("de_AT@foo"), Some(Some(Locale::de_AT)));
+        assert_eq!(parse_locale("C.UTF-8"), Some(None));
+        assert_eq!(parse_locale("POSIX"), Some(None));
+        assert_eq!(parse_locale("xx_YY"), None);
+    }
+    #[test]
+    fn test_resolve_locale_setting() {
+        assert_eq!(resolve_locale(Some("fr_FR")), Ok(Some(Locale::fr_FR)));
+        assert_eq!(resolve_locale(Some("C")), Ok(None));
+        assert!(resolve_locale(Some("klingon")).is_err());
+    }
+    #[test]
+    fn test_starts_with_sunday() {
+        assert!(starts_with_sunday(Locale::en_US));
+        assert!(!starts_with_sunday(Locale::de_AT));
+        assert!(!starts_with_sunday(Locale::en_GB));
+    }
+}
@@ -2,8 +2,20 @@
 //
 // SPDX-License-Identifier: MIT
 
+mod astronomy;
+mod background;
 mod date_extensions;
+mod date_parser;
+mod date_span;
 mod helpers;
+mod locale;
+mod sun;
 
+pub use astronomy::{moon_phase, moon_phases, season, seasons, MoonPhase, Season};
+pub use background::detect_styletype;
 pub use date_extensions::DateExtensions;
-pub use helpers::tostyle;
+pub use date_parser::{parse_date, DatePrecision, ParsedDate};
+pub use date_span::DateSpan;
+pub use helpers::{combine_styles, countdown, tostyle};
+pub use locale::{resolve_locale, starts_with_sunday};
+pub use sun::{daylight, Daylight};
@@ -0,0 +1,96 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+//! Sunrise and sunset, computed using the general solar position
+//! equations of the NOAA Global Monitoring Division. The times are
+//! accurate to about a minute.
+
+use chrono::prelude::*;
+use chrono::Duration;
+use std::f64::consts::PI;
+
+/// The sunrise, the sunset and the length of the day at a location.
+/// During polar day and polar night there is no sunrise and no sunset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Daylight {
+    pub sunrise: Option<DateTime<Local>>,
+    pub sunset: Option<DateTime<Local>>,
+    pub day_length: Duration,
+}
+
+/// Compute the daylight on `date` at the location with the latitude
+/// `lat` and the longitude `lon` (in degrees, east and north are
+/// positive).
+pub fn daylight(date: NaiveDate, lat: f64, lon: f64) -> Daylight {
+    let gamma = 2.0 * PI / f64::from(if date.leap_year() { 366 } else { 365 }) * f64::from(date.ordinal0());
+    let eqtime = 229.18
+        * (0.000075 + 0.001868 * gamma.cos() - 0.032077 * gamma.sin() - 0.014615 * (2.0 * gamma).cos() - 0.040849 * (2.0 * gamma).sin());
+    let decl = 0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin() - 0.006758 * (2.0 * gamma).cos() + 0.000907 * (2.0 * gamma).sin()
+        - 0.002697 * (3.0 * gamma).cos()
+        + 0.00148 * (3.0 * gamma).sin();
+    // The zenith of the sunrise and the sunset includes the refraction
+    // and the size of the solar disk
+    let lat = lat.to_radians();
+    let cos_ha = 90.833_f64.to_radians().cos() / (lat.cos() * decl.cos()) - lat.tan() * decl.tan();
+    if cos_ha > 1.0 {
+        return Daylight {
+            sunrise: None,
+            sunset: None,
+            day_length: Duration::zero(),
+        };
+    }
+    if cos_ha < -1.0 {
+        return Daylight {
+            sunrise: None,
+            sunset: None,
+            day_length: Duration::days(1),
+        };
+    }
+    let ha = cos_ha.acos().to_degrees();
+    let midnight = date.and_time(NaiveTime::MIN).and_utc();
+    let minutes = |minutes: f64| midnight + Duration::seconds((minutes * 60.0).round() as i64);
+    let sunrise = minutes(720.0 - 4.0 * (lon + ha) - eqtime);
+    let sunset = minutes(720.0 - 4.0 * (lon - ha) - eqtime);
+    Daylight {
+        sunrise: Some(sunrise.with_timezone(&Local)),
+        sunset: Some(sunset.with_timezone(&Local)),
+        day_length: sunset - sunrise,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Option<DateTime<Local>>, expected: &str) {
+        let expected: DateTime<Utc> = expected.parse().unwrap();
+        let actual = actual.unwrap().with_timezone(&Utc);
+        assert!((actual - expected).num_minutes().abs() <= 2, "{} is not close to {}", actual, expected);
+    }
+
+    #[test]
+    fn test_daylight_vienna() {
+        let date = NaiveDate::from_ymd_opt(2025, 9, 9).unwrap();
+        let daylight = daylight(date, 48.2082, 16.3738);
+        assert_close(daylight.sunrise, "2025-09-09T04:22:00Z");
+        assert_close(daylight.sunset, "2025-09-09T17:21:00Z");
+        assert!((daylight.day_length.num_minutes() - 779).abs() <= 2);
+    }
+    #[test]
+    fn test_daylight_west() {
+        let date = NaiveDate::from_ymd_opt(2025, 12, 21).unwrap();
+        let daylight = daylight(date, 40.7128, -74.006);
+        assert_close(daylight.sunrise, "2025-12-21T12:16:00Z");
+        assert_close(daylight.sunset, "2025-12-21T21:32:00Z");
+    }
+    #[test]
+    fn test_daylight_polar() {
+        let polar_night = daylight(NaiveDate::from_ymd_opt(2025, 12, 21).unwrap(), 78.22, 15.65);
+        assert_eq!(polar_night.sunrise, None);
+        assert_eq!(polar_night.day_length, Duration::zero());
+        let polar_day = daylight(NaiveDate::from_ymd_opt(2025, 6, 21).unwrap(), 78.22, 15.65);
+        assert_eq!(polar_day.sunset, None);
+        assert_eq!(polar_day.day_length, Duration::days(1));
+    }
+}
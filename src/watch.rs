@@ -0,0 +1,167 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+//! The watch mode: the output is rendered again whenever one of the
+//! files it is made of changes or when the day changes. The files are
+//! polled, so this works without any support of the operating system.
+
+use crate::cli::Cli;
+use crate::config::{Config, Theme};
+use crate::context::Context;
+use crate::error::Error;
+use crate::template::render;
+use chrono::prelude::*;
+use clap::crate_name;
+use crossterm::cursor::MoveTo;
+use crossterm::execute;
+use crossterm::terminal::{Clear, ClearType};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Render the output again and again, until carl is interrupted.
+#[cfg(not(tarpaulin_include))]
+pub fn run(opts: Cli) -> Result<(), Error> {
+    loop {
+        let today = Local::now().date_naive();
+        execute!(io::stdout(), Clear(ClearType::All), MoveTo(0, 0))?;
+        // Problems are shown instead of the output, they might be fixed
+        // by the next change of the files
+        let files = match Context::from_opts(opts.clone()) {
+            Ok(ctx) => {
+                for warning in &ctx.warnings {
+                    eprintln!("{}: warning: {}", crate_name!(), warning);
+                }
+                match render(&ctx) {
+                    Ok(x) => print!("{}", x),
+                    Err(e) => eprintln!("{}: {}", crate_name!(), e),
+                }
+                watched_files(&ctx)
+            }
+            Err(e) => {
+                eprintln!("{}: {}", crate_name!(), e);
+                config_files(&opts.config)
+            }
+        };
+        let state = modification_times(&files);
+        while state == modification_times(&files) && today == Local::now().date_naive() {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// The files the output of `ctx` depends on: the configuration files,
+/// the themefiles, the ics files and the templates.
+fn watched_files(ctx: &Context) -> Vec<PathBuf> {
+    let mut files = config_files(&ctx.opts.config);
+    let themenames = if ctx.opts.theme.is_empty() { &ctx.config.theme } else { &ctx.opts.theme };
+    files.append(&mut theme_files(themenames));
+    for icalstyle in &ctx.config.ical {
+        files.append(&mut directory_files(Path::new(&icalstyle.file)));
+    }
+    if let Some(path) = ctx.config.template() {
+        files.append(&mut directory_files(&path));
+    }
+    files
+}
+
+/// The configuration files including the files they include. The file
+/// of the user is also watched if it does not exist yet.
+fn config_files(config_file: &Option<PathBuf>) -> Vec<PathBuf> {
+    let mut files = Config::files(config_file);
+    if let Some(path) = config_file.clone().or_else(Config::path) {
+        if !files.contains(&path) {
+            files.push(path);
+        }
+    }
+    let mut index = 0;
+    while let Some(path) = files.get(index).cloned() {
+        index += 1;
+        let Some(table) = fs::read_to_string(&path).ok().and_then(|content| content.parse::<toml::Table>().ok()) else {
+            continue;
+        };
+        for include in Config::includes(&path, &table).unwrap_or_default() {
+            if !files.contains(&include) {
+                files.push(include);
+            }
+        }
+    }
+    files
+}
+
+/// The themefiles of the themes `themenames` and of the themes they
+/// extend. For builtin themes the file that would override them is
+/// watched.
+fn theme_files(themenames: &[String]) -> Vec<PathBuf> {
+    let mut themes: Vec<String> = themenames.to_vec();
+    let mut files = vec![];
+    let mut index = 0;
+    while let Some(themename) = themes.get(index).cloned() {
+        index += 1;
+        let Ok(Some((origin, content))) = Theme::find(&themename) else {
+            continue;
+        };
+        if origin == "builtin" {
+            files.extend(Theme::path(&themename));
+            continue;
+        }
+        files.push(PathBuf::from(&origin));
+        for parent in toml::from_str::<Theme>(&content).map(|theme| theme.extends).unwrap_or_default() {
+            let parent = Theme::parent(&origin, parent);
+            if parent != themename && !themes.contains(&parent) {
+                themes.push(parent);
+            }
+        }
+    }
+    files
+}
+
+/// `path` and, if it is a directory, all the files in it.
+fn directory_files(path: &Path) -> Vec<PathBuf> {
+    let mut files = vec![path.to_path_buf()];
+    if let Ok(entries) = path.read_dir() {
+        for entry in entries.flatten() {
+            files.append(&mut directory_files(&entry.path()));
+        }
+    }
+    files
+}
+
+fn modification_times(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files.iter().map(|path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::IcalStyle;
+
+    #[test]
+    fn test_watched_files() {
+        let data = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/data"));
+        let mut ctx = Context::default();
+        ctx.opts.config = Some(data.join("config.toml"));
+        ctx.opts.theme = vec![format!("{}/themes/mono.theme", env!("CARGO_MANIFEST_DIR"))];
+        ctx.config.ical = vec![IcalStyle {
+            file: data.join("carl.ics").display().to_string(),
+            ..Default::default()
+        }];
+        ctx.config.template_dir = Some(format!("{}/templates", env!("CARGO_MANIFEST_DIR")));
+        let files = watched_files(&ctx);
+        assert!(files.contains(&data.join("config.toml")));
+        assert!(files.iter().any(|path| path.ends_with("mono.theme")));
+        assert!(files.contains(&data.join("carl.ics")));
+        assert!(files.iter().any(|path| path.ends_with("templates/carl.tmpl")));
+    }
+    #[test]
+    fn test_modification_times() {
+        let files = [PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml")), PathBuf::from("/nonexistent")];
+        let times = modification_times(&files);
+        assert!(times[0].is_some());
+        assert!(times[1].is_none());
+    }
+}
@@ -0,0 +1,159 @@
+// SPDX-FileCopyrightText: 2025 Birger Schacht <birger@rantanplan.org>
+//
+// SPDX-License-Identifier: MIT
+
+// Golden tests for the rendered output of carl.
+//
+// Every `.test` file in `tests/testfiles` contains the commandline
+// arguments in the first line and the expected output in the
+// remaining lines. The tests are rendered with the configuration and
+// the ics file from the `data` directory, the default theme from the
+// `themes` directory, with 2025-09-09 as today and in the UTC timezone.
+// Set `CARL_BLESS=1` to overwrite the expected output with the actual
+// output. The tests run without the test harness (see `main`).
+
+use carl::{render, render_upcoming, Cli, Command, Config, Context, Location, Theme};
+use clap::Parser;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TODAY: &str = "2025-09-09";
+
+fn data_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("data")
+}
+
+fn golden_config() -> Config {
+    let mut config: Config = toml::from_str(&fs::read_to_string(data_dir().join("config.toml")).unwrap()).unwrap();
+    for icalstyle in &mut config.ical {
+        icalstyle.file = data_dir().join(&icalstyle.file).display().to_string();
+    }
+    // The expected output uses english names and ISO dates
+    config.locale = Some(String::from("C"));
+    // --daylight shows the times of the sunrise and the sunset in Vienna
+    config.location = Some(Location { lat: 48.2082, lon: 16.3738 });
+    config
+}
+
+fn golden_theme() -> Theme {
+    let theme_file = Path::new(env!("CARGO_MANIFEST_DIR")).join("themes").join("default.theme");
+    Theme::read(&[theme_file.display().to_string()], &mut vec![]).unwrap()
+}
+
+fn find_testfiles(dir: &Path, testfiles: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            find_testfiles(&path, testfiles);
+        } else if path.extension().is_some_and(|extension| extension == "test") {
+            testfiles.push(path);
+        }
+    }
+}
+
+fn render_arguments(arguments: &str) -> Result<String, String> {
+    let mut opts = Cli::try_parse_from(std::iter::once("carl").chain(arguments.split_whitespace()))
+        .map_err(|e| e.to_string())?;
+    if opts.today.is_none() {
+        opts.today = Some(TODAY.parse().unwrap());
+    }
+    let ctx = Context::from_parts(opts, golden_config(), golden_theme()).map_err(|e| e.to_string())?;
+    match ctx.opts.command {
+        Some(Command::Upcoming { count }) => render_upcoming(&ctx, count),
+        _ => render(&ctx),
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// A line based diff of `expected` and `actual`, based on the longest
+/// common subsequence of the lines. Escape sequences are made visible.
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut lcs = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut ret = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            ret.push_str(&format!("  {}\n", expected[i].escape_debug()));
+            i += 1;
+            j += 1;
+        } else if i < expected.len() && (j == actual.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ret.push_str(&format!("- {}\n", expected[i].escape_debug()));
+            i += 1;
+        } else {
+            ret.push_str(&format!("+ {}\n", actual[j].escape_debug()));
+            j += 1;
+        }
+    }
+    ret
+}
+
+fn test_golden_files() {
+    let bless = std::env::var("CARL_BLESS").is_ok_and(|x| !x.is_empty() && x != "0");
+    let mut testfiles = vec![];
+    find_testfiles(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/testfiles"), &mut testfiles);
+    testfiles.sort();
+    assert!(!testfiles.is_empty());
+
+    let mut failures = vec![];
+    for testfile in &testfiles {
+        let content = fs::read_to_string(testfile).unwrap();
+        let (arguments, expected) = content.split_once('\n').unwrap_or((&content, ""));
+        let actual = match render_arguments(arguments) {
+            Ok(x) => x,
+            Err(e) => {
+                failures.push(format!("{} ({}): {}", testfile.display(), arguments, e));
+                continue;
+            }
+        };
+        if actual == expected {
+            continue;
+        }
+        if bless {
+            fs::write(testfile, format!("{}\n{}", arguments, actual)).unwrap();
+        } else {
+            failures.push(format!(
+                "{} ({}):\n--- expected\n+++ actual\n{}",
+                testfile.display(),
+                arguments,
+                diff(expected, &actual)
+            ));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{} of {} golden files differ (set CARL_BLESS=1 to update them):\n\n{}",
+        failures.len(),
+        testfiles.len(),
+        failures.join("\n")
+    );
+}
+
+fn test_diff() {
+    assert_eq!(diff("a\nb\nc", "a\nx\nc"), "  a\n- b\n+ x\n  c\n");
+    assert_eq!(diff("a", "a\nb"), "  a\n+ b\n");
+}
+
+// The tests run without the test harness, so the environment is set
+// before anything runs in another thread.
+fn main() {
+    // Times like the sunrise are shown in the local timezone
+    std::env::set_var("TZ", "UTC");
+    // The options are only those of the golden files
+    std::env::remove_var("CARL_CONFIG");
+    std::env::remove_var("CARL_TODAY");
+    // The themes extended by the default theme are the builtin ones
+    std::env::set_var("XDG_CONFIG_HOME", "/nonexistent");
+    test_diff();
+    test_golden_files();
+}
@@ -14,11 +14,15 @@ TMPHOME=$($MKTEMP -d)
 CONFIG=$TMPHOME/.config/carl
 mkdir -p $CONFIG
 echo $CONFIG
-cp $DATADIR/config.toml $DATADIR/carl.ics $DATADIR/default.theme $CONFIG
+cp $DATADIR/config.toml $DATADIR/carl.ics $CONFIG
 sed -i "s#file = \"carl.ics\"#file = \"$CONFIG/carl.ics\"#" $CONFIG/config.toml
+printf '[location]\nlat = 48.2082\nlon = 16.3738\n' >> $CONFIG/config.toml
 cat $CONFIG/*
 
-FAKETIME='2025-09-09 17:47:30'
+TODAY='2025-09-09'
+# The expected output uses english names and ISO dates
+export LC_ALL=C
+export TZ=UTC
 
 cargo build --release
 
@@ -27,7 +31,7 @@ for file in $(find $TESTDIR -name '*.test'); do
   TESTAGAINSTFILE=$($MKTEMP)
   tail -n +2 $file > $TESTAGAINSTFILE
   TESTOUTPUT=$($MKTEMP)
-  XDG_CONFIG_HOME=$TMPHOME/.config faketime "$FAKETIME" ./target/release/carl $ARGUMENTS > $TESTOUTPUT
+  XDG_CONFIG_HOME=$TMPHOME/.config CARL_TODAY="$TODAY" ./target/release/carl $ARGUMENTS > $TESTOUTPUT
   diff $TESTOUTPUT $TESTAGAINSTFILE
   rm -f $TESTOUTPUT $TESTAGAINSTFILE
 done